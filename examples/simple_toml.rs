@@ -1,16 +1,23 @@
 use std::{sync::LazyLock, time::Duration};
 
 use anyhow::Result;
-use china_unicom_rs::{data::ChinaUnicomData, query::query_china_unicom_data};
+use china_unicom_rs::{
+    data::ChinaUnicomData, ChinaUnicomClient, CredentialStore, EncryptedCredentialStore, SecretKey,
+    Template,
+};
 use chrono::TimeDelta;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Config {
-    // 中国联通cookie
+    // 中国联通cookie(设置了 secret_file 时可省略)
+    #[serde(default)]
     pub cookie: String,
+    // 加密的凭据文件, 口令从环境变量 CHINA_UNICOM_PASSPHRASE 读取
+    #[serde(default)]
+    pub secret_file: Option<String>,
     // bark key
     pub key: String,
     // 请求间隔(s)
@@ -27,6 +34,21 @@ pub struct Config {
     pub timeout: Option<i64>,
 }
 
+// 不输出 cookie 与 bark key, 避免泄露到日志
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("cookie", &"<redacted>")
+            .field("secret_file", &self.secret_file)
+            .field("key", &"<redacted>")
+            .field("interval", &self.interval)
+            .field("free_threshold", &self.free_threshold)
+            .field("non_threshold", &self.non_threshold)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
 fn default_interval() -> u64 {
     60
 }
@@ -39,15 +61,31 @@ fn default_timeout() -> Option<i64> {
     Some(1800)
 }
 
-const FORMAT1: &'static str = "[区间时长] 跳: [区间流量收费用量], 免: [区间流量免费用量]";
+static FORMAT1: LazyLock<Template> = LazyLock::new(|| {
+    Template::compile("[区间时长] 跳: [区间流量收费用量], 免: [区间流量免费用量]")
+        .expect("模板无效")
+});
 
-const FORMAT2: &'static str = "今跳:[区间流量收费用量], 今免: [区间流量免费用量]";
+static FORMAT2: LazyLock<Template> = LazyLock::new(|| {
+    Template::compile("今跳:[区间流量收费用量], 今免: [区间流量免费用量]").expect("模板无效")
+});
 
-const FORMAT3: &'static str = "通用余: [流量通用余量], 定向余: [流量定向余量]";
+static FORMAT3: LazyLock<Template> = LazyLock::new(|| {
+    Template::compile("通用余: [流量通用余量], 定向余: [流量定向余量]").expect("模板无效")
+});
 
-const FORMAT4: &'static str = "通用已用: [流量通用用量], 定向已用: [流量定向用量]";
+static FORMAT4: LazyLock<Template> = LazyLock::new(|| {
+    Template::compile("通用已用: [流量通用用量], 定向已用: [流量定向用量]").expect("模板无效")
+});
 
-static CLIENT: LazyLock<Client> = LazyLock::new(|| Client::new());
+static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+static UNICOM: LazyLock<ChinaUnicomClient> = LazyLock::new(|| {
+    ChinaUnicomClient::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap()
+});
 
 fn format_cachefile_name(date: chrono::NaiveDate) -> String {
     date.format("china_unicom_%Y-%m-%d.json").to_string()
@@ -55,7 +93,17 @@ fn format_cachefile_name(date: chrono::NaiveDate) -> String {
 
 fn load_config() -> Result<Config> {
     let config = std::fs::read_to_string("config.toml")?;
-    let config: Config = toml::from_str(&config)?;
+    let mut config: Config = toml::from_str(&config)?;
+    if let Some(secret_file) = &config.secret_file {
+        let passphrase = std::env::var("CHINA_UNICOM_PASSPHRASE")?;
+        let store = EncryptedCredentialStore::new(secret_file, SecretKey::passphrase(passphrase));
+        let credentials = store
+            .load()?
+            .ok_or_else(|| anyhow::anyhow!("加密凭据文件不存在: {secret_file}"))?;
+        config.cookie = credentials
+            .cookie
+            .ok_or_else(|| anyhow::anyhow!("加密凭据文件中没有 cookie"))?;
+    }
     Ok(config)
 }
 
@@ -75,28 +123,28 @@ async fn save_data(data: &ChinaUnicomData) -> Result<()> {
 }
 
 async fn format_message(data: &ChinaUnicomData, lastdata: &ChinaUnicomData) -> Result<String> {
-    let mut message = data.format_with_last(&FORMAT1, lastdata)?;
+    let mut message = FORMAT1.render_with_last(data, lastdata)?;
     message += "%0a";
     let yesterday = data.time.date_naive() - chrono::Duration::days(1);
     if let Ok(yesterday_data) = load_data(yesterday).await {
-        message += &data.format_with_last(&FORMAT2, &yesterday_data)?;
+        message += &FORMAT2.render_with_last(data, &yesterday_data)?;
         message += "%0a";
     } else {
-        message += &data.format(&FORMAT4)?;
+        message += &FORMAT4.render(data)?;
         message += "%0a";
     };
-    message += &data.format(&FORMAT3)?;
+    message += &FORMAT3.render(data)?;
     Ok(message)
 }
 
 async fn format_first_message(data: &ChinaUnicomData) -> Result<String> {
-    let mut message = data.format(&FORMAT3)?;
+    let mut message = FORMAT3.render(data)?;
     message += "%0a";
-    message += &data.format(&FORMAT4)?;
+    message += &FORMAT4.render(data)?;
     let yesterday = data.time.date_naive() - chrono::Duration::days(1);
     if let Ok(yesterday_data) = load_data(yesterday).await {
         message += "%0a";
-        message += &data.format_with_last(&FORMAT2, &yesterday_data)?;
+        message += &FORMAT2.render_with_last(data, &yesterday_data)?;
     };
     Ok(message)
 }
@@ -121,17 +169,25 @@ fn should_notify(config: &Config, data: &ChinaUnicomData, lastdata: &ChinaUnicom
             return true;
         }
     }
+    // 开始产生超出费用时立即通知
+    if data.is_over_quota() && !lastdata.is_over_quota() {
+        return true;
+    }
+    // 开始限速或解除限速时立即通知
+    if (data - lastdata).throttle_change.is_some() {
+        return true;
+    }
     if let Some(free_threshold) = config.free_threshold {
-        if data.free_flow_used - lastdata.free_flow_used >= free_threshold {
+        if (data.free_flow_used - lastdata.free_flow_used).as_gb() >= free_threshold {
             return true;
         }
     }
     if let Some(nonfree_threshold) = config.non_threshold {
-        if data.non_free_flow_used - lastdata.non_free_flow_used >= nonfree_threshold {
+        if (data.non_free_flow_used - lastdata.non_free_flow_used).as_gb() >= nonfree_threshold {
             return true;
         }
     }
-    return false;
+    false
 }
 
 // 首先获取一个新的数据，然后减去上次获取(第一次没有)，以及昨日获取(必有)
@@ -143,7 +199,10 @@ async fn main() {
     let mut last_data = {
         match load_data(chrono::Local::now().naive_local().into()).await {
             Err(_) => {
-                let data = query_china_unicom_data(&config.cookie).await.unwrap();
+                let data = UNICOM
+                    .query_china_unicom_data(&config.cookie)
+                    .await
+                    .unwrap();
                 match format_first_message(&data).await {
                     Ok(message) => {
                         let _ = notify(&config.key, &data.package_name, &message).await;
@@ -163,7 +222,7 @@ async fn main() {
     let interval = Duration::from_secs(config.interval);
 
     loop {
-        match query_china_unicom_data(&config.cookie).await {
+        match UNICOM.query_china_unicom_data(&config.cookie).await {
             Ok(data) => match format_message(&data, &last_data).await {
                 Ok(message) => {
                     println!("{}", message);
@@ -0,0 +1,219 @@
+use std::{
+    fmt::{self, Display},
+    iter::Sum,
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+// 各单位之间按 1024 换算
+const UNIT: f64 = 1024.0;
+
+// 流量数量, 以整数 KB 保存, 避免 f64 累加误差
+// 序列化为 KB 整数; 反序列化时小数视为旧版本保存的 GB 数值, 以便读取旧的查询记录
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct DataAmount(i64);
+
+impl<'de> Deserialize<'de> for DataAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DataAmountVisitor)
+    }
+}
+
+struct DataAmountVisitor;
+
+impl Visitor<'_> for DataAmountVisitor {
+    type Value = DataAmount;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("以 KB 为单位的整数, 或旧版本以 GB 为单位的小数")
+    }
+
+    fn visit_i64<E: de::Error>(self, kb: i64) -> Result<DataAmount, E> {
+        Ok(DataAmount::from_kb(kb))
+    }
+
+    fn visit_u64<E: de::Error>(self, kb: u64) -> Result<DataAmount, E> {
+        i64::try_from(kb)
+            .map(DataAmount::from_kb)
+            .map_err(|_| E::custom(format!("流量数值过大: {kb}")))
+    }
+
+    // 旧版本的 ChinaUnicomData 以 f64 保存 GB 数值
+    fn visit_f64<E: de::Error>(self, gb: f64) -> Result<DataAmount, E> {
+        Ok(DataAmount::from_gb(gb))
+    }
+}
+
+impl DataAmount {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_kb(kb: i64) -> Self {
+        Self(kb)
+    }
+
+    // 不足 1KB 的部分四舍五入
+    pub fn from_bytes(bytes: i64) -> Self {
+        Self((bytes as f64 / UNIT).round() as i64)
+    }
+
+    pub fn from_mb(mb: f64) -> Self {
+        Self((mb * UNIT).round() as i64)
+    }
+
+    pub fn from_gb(gb: f64) -> Self {
+        Self::from_mb(gb * UNIT)
+    }
+
+    pub fn from_tb(tb: f64) -> Self {
+        Self::from_gb(tb * UNIT)
+    }
+
+    pub const fn as_kb(self) -> i64 {
+        self.0
+    }
+
+    pub const fn as_bytes(self) -> i64 {
+        self.0 * 1024
+    }
+
+    pub fn as_mb(self) -> f64 {
+        self.0 as f64 / UNIT
+    }
+
+    pub fn as_gb(self) -> f64 {
+        self.as_mb() / UNIT
+    }
+
+    pub fn as_tb(self) -> f64 {
+        self.as_gb() / UNIT
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+// 自动选择 KB/MB/GB/TB 中最合适的单位, 默认保留两位小数, 可用 {:.1} 等指定精度
+impl Display for DataAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kb = self.0.unsigned_abs() as f64;
+        let precision = f.precision().unwrap_or(2);
+        if kb < UNIT {
+            write!(f, "{}KB", self.0)
+        } else if kb < UNIT * UNIT {
+            write!(f, "{:.*}MB", precision, self.as_mb())
+        } else if kb < UNIT * UNIT * UNIT {
+            write!(f, "{:.*}GB", precision, self.as_gb())
+        } else {
+            write!(f, "{:.*}TB", precision, self.as_tb())
+        }
+    }
+}
+
+impl Add for DataAmount {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for DataAmount {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for DataAmount {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for DataAmount {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for DataAmount {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl Sum for DataAmount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a DataAmount> for DataAmount {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_as_kb_integer() {
+        let amount = DataAmount::from_mb(1.5);
+
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "1536");
+    }
+
+    #[test]
+    fn round_trip_keeps_kb() {
+        let amount = DataAmount::from_kb(123_457);
+        let json = serde_json::to_string(&amount).unwrap();
+
+        assert_eq!(serde_json::from_str::<DataAmount>(&json).unwrap(), amount);
+    }
+
+    #[test]
+    fn integer_is_kb() {
+        let amount: DataAmount = serde_json::from_str("2048").unwrap();
+
+        assert_eq!(amount, DataAmount::from_mb(2.0));
+        let amount: DataAmount = serde_json::from_str("-1024").unwrap();
+        assert_eq!(amount, DataAmount::from_mb(-1.0));
+    }
+
+    #[test]
+    fn legacy_float_is_gb() {
+        // 旧版本把 10GB 保存为 10.0
+        let amount: DataAmount = serde_json::from_str("10.0").unwrap();
+        assert_eq!(amount, DataAmount::from_gb(10.0));
+
+        let amount: DataAmount = serde_json::from_str("0.5").unwrap();
+        assert_eq!(amount, DataAmount::from_mb(512.0));
+    }
+
+    #[test]
+    fn legacy_float_round_trips_as_kb() {
+        let amount: DataAmount = serde_json::from_str("1.25").unwrap();
+        let json = serde_json::to_string(&amount).unwrap();
+
+        assert_eq!(json, (1024 * 1024 + 256 * 1024).to_string());
+        assert_eq!(serde_json::from_str::<DataAmount>(&json).unwrap(), amount);
+    }
+
+    #[test]
+    fn other_types_are_rejected() {
+        assert!(serde_json::from_str::<DataAmount>("\"10\"").is_err());
+        assert!(serde_json::from_str::<DataAmount>("null").is_err());
+        assert!(serde_json::from_str::<DataAmount>(&u64::MAX.to_string()).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    fmt::{self, Debug},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client, Proxy, Url,
+};
+use rsa::RsaPublicKey;
+
+use crate::cookie_jar::CookieJar;
+use crate::error::{Error, Result};
+use crate::login::{parse_public_key, DEFAULT_LOGIN_PUBLIC_KEY};
+
+// 默认的接口地址
+pub const DEFAULT_BASE_URL: &str = "https://m.client.10010.com";
+
+// 覆盖默认接口地址的环境变量, 用于测试或预发环境
+pub const BASE_URL_ENV: &str = "CHINA_UNICOM_BASE_URL";
+
+// 默认的 User-Agent, 与联通 iPhone 客户端保持一致
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 unicom{version:iphone_c@11.0700}";
+
+// 模块级函数共用的默认客户端, 构建失败(如环境变量中的接口地址无效)时保存错误信息
+static DEFAULT_CLIENT: LazyLock<std::result::Result<ChinaUnicomClient, String>> =
+    LazyLock::new(|| ChinaUnicomClient::new().map_err(|e| e.to_string()));
+
+// 模块级函数使用的默认客户端, 无法构建时返回 Error::Config
+pub(crate) fn default_client() -> Result<&'static ChinaUnicomClient> {
+    DEFAULT_CLIENT
+        .as_ref()
+        .map_err(|e| Error::Config(format!("无法构建默认客户端: {e}")))
+}
+
+// 可复用的联通客户端, 内部的连接池在 clone 之间共享
+#[derive(Clone)]
+pub struct ChinaUnicomClient {
+    pub(crate) http: Client,
+    pub(crate) base_url: String,
+    // 密码登录时加密手机号与密码的公钥
+    pub(crate) login_public_key: RsaPublicKey,
+    // 设置后所有请求自动携带并记录 cookie
+    pub(crate) cookie_jar: Option<CookieJar>,
+}
+
+// reqwest 客户端的 Debug 会输出默认请求头, 这里省略
+impl Debug for ChinaUnicomClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChinaUnicomClient")
+            .field("base_url", &self.base_url)
+            .field("cookie_jar", &self.cookie_jar)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChinaUnicomClient {
+    // 使用默认配置创建客户端, 环境变量中的接口地址无效时返回 Error::Config
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> ChinaUnicomClientBuilder {
+        ChinaUnicomClientBuilder::default()
+    }
+
+    // 底层的 reqwest 客户端
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_ref()
+    }
+
+    // 由接口地址和路径拼接出完整的接口 url
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+}
+
+pub struct ChinaUnicomClientBuilder {
+    // 已有的 reqwest 客户端, 设置后忽略其余的连接配置
+    client: Option<Client>,
+    base_url: String,
+    user_agent: String,
+    headers: HeaderMap,
+    proxies: Vec<Proxy>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    login_public_key: String,
+    cookie_jar: Option<CookieJar>,
+}
+
+// 请求头中可能有 cookie、token, 代理中可能有账号密码, Debug 输出只显示请求头名称与代理数量
+// reqwest 客户端的 Debug 同样会输出请求头, 因此只显示是否设置
+impl Debug for ChinaUnicomClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChinaUnicomClientBuilder")
+            .field("client", &self.client.is_some())
+            .field("base_url", &self.base_url)
+            .field("user_agent", &self.user_agent)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("proxies", &self.proxies.len())
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("login_public_key", &self.login_public_key)
+            .field("cookie_jar", &self.cookie_jar)
+            .finish()
+    }
+}
+
+impl Default for ChinaUnicomClientBuilder {
+    fn default() -> Self {
+        Self {
+            client: None,
+            base_url: env_base_url().unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: HeaderMap::new(),
+            proxies: Vec::new(),
+            timeout: None,
+            connect_timeout: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            login_public_key: DEFAULT_LOGIN_PUBLIC_KEY.to_string(),
+            cookie_jar: None,
+        }
+    }
+}
+
+impl ChinaUnicomClientBuilder {
+    // 复用一个已有的 reqwest 客户端(及其连接池), 此时无法再设置 cookie_jar, 需在该客户端上自行配置
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    // 覆盖接口地址(优先于环境变量), 所有接口都会基于此地址请求
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    // 添加一个随每个请求发送的请求头
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    // 整个请求(含读取响应)的超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    // 连接池中空闲连接的保留时间
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    // 替换密码登录使用的公钥, 支持 PEM 或 base64 编码的 DER
+    pub fn login_public_key(mut self, key: impl Into<String>) -> Self {
+        self.login_public_key = key.into();
+        self
+    }
+
+    // 使用 cookie jar 记录并自动携带 cookie
+    pub fn cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.cookie_jar = Some(jar);
+        self
+    }
+
+    pub fn build(self) -> Result<ChinaUnicomClient> {
+        let login_public_key = parse_public_key(&self.login_public_key)?;
+        let base_url = Url::parse(&self.base_url)
+            .map_err(|e| Error::Config(format!("接口地址 {} 无效: {e}", self.base_url)))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(Error::Config(format!(
+                "不支持的接口地址: {}",
+                self.base_url
+            )));
+        }
+
+        let http = match self.client {
+            // 已有的客户端无法再接入 cookie jar, 静默忽略会导致 cookie 既不携带也不保存
+            Some(_) if self.cookie_jar.is_some() => {
+                return Err(Error::Config(
+                    "cookie_jar 不能与已有的 reqwest 客户端同时使用".to_string(),
+                ))
+            }
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder()
+                    .user_agent(self.user_agent)
+                    .default_headers(self.headers);
+                if let Some(jar) = &self.cookie_jar {
+                    builder = builder.cookie_provider(jar.provider());
+                }
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.pool_idle_timeout {
+                    builder = builder.pool_idle_timeout(timeout);
+                }
+                if let Some(max) = self.pool_max_idle_per_host {
+                    builder = builder.pool_max_idle_per_host(max);
+                }
+                builder.build()?
+            }
+        };
+
+        Ok(ChinaUnicomClient {
+            http,
+            base_url: base_url.as_str().trim_end_matches('/').to_string(),
+            login_public_key,
+            cookie_jar: self.cookie_jar,
+        })
+    }
+}
+
+fn env_base_url() -> Option<String> {
+    std::env::var(BASE_URL_ENV)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+}
@@ -0,0 +1,274 @@
+use std::{
+    fmt::{self, Debug},
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, MutexGuard},
+};
+
+use chrono::{DateTime, Local};
+use cookie_store::{CookieExpiration, CookieStore, RawCookie};
+use reqwest::Url;
+use reqwest_cookie_store::CookieStoreMutex;
+
+use crate::error::{Error, Result};
+use crate::secret::{decrypt, encrypt, SecretKey, REDACTED};
+use crate::store::write_atomic;
+
+// 登录后用于查询的两个 cookie
+pub const ECS_COOKIES: [&str; 2] = ["ecs_token", "ecs_acc"];
+
+// 记录接口下发的全部 cookie(含过期时间、域名), 由客户端在每个请求中自动携带
+// clone 之间共享同一份 cookie
+#[derive(Clone, Default)]
+pub struct CookieJar {
+    store: Arc<CookieStoreMutex>,
+    // 设置后 persist() 会用此密钥加密写入此文件
+    file: Option<(PathBuf, SecretKey)>,
+}
+
+impl Debug for CookieJar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieJar")
+            .field("cookies", &self.cookies())
+            .field("path", &self.path())
+            .finish()
+    }
+}
+
+// 单个 cookie 的信息
+#[derive(Clone, PartialEq)]
+pub struct CookieInfo {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+    pub path: String,
+    // 会话 cookie 没有过期时间
+    pub expires: Option<DateTime<Local>>,
+}
+
+impl Debug for CookieInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieInfo")
+            .field("name", &self.name)
+            .field("value", &REDACTED)
+            .field("domain", &self.domain)
+            .field("path", &self.path)
+            .field("expires", &self.expires)
+            .finish()
+    }
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 从加密的文件中加载 cookie(含会话 cookie, 与 save() 对应), 文件不存在时创建空的 cookie jar,
+    // 之后 persist() 用同一密钥写回同一文件, 密钥错误或文件被篡改时返回 Error::Secret
+    pub fn open(path: impl Into<PathBuf>, key: SecretKey) -> Result<Self> {
+        let path = path.into();
+        let store = match fs::read_to_string(&path) {
+            Ok(content) => {
+                let content = decrypt(&content, &key)?;
+                cookie_store::serde::json::load_all(content.as_slice()).map_err(|e| {
+                    Error::Store(format!("无法解析 cookie 文件 {}: {e}", path.display()))
+                })?
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => CookieStore::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            store: Arc::new(CookieStoreMutex::new(store)),
+            file: Some((path, key)),
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    // 保存到 open() 时的文件, 未关联文件时不做任何事
+    pub fn persist(&self) -> Result<()> {
+        match &self.file {
+            Some((path, key)) => self.save(path, key),
+            None => Ok(()),
+        }
+    }
+
+    // 加密保存全部 cookie(含会话 cookie), 权限为 0600
+    // ecs cookie 与凭据一样可以直接登录, 因此使用与 EncryptedCredentialStore 相同的加密格式
+    // 联通下发的 ecs cookie 可能没有过期时间, 只保存持久 cookie 会在重启后丢失登录状态
+    pub fn save(&self, path: impl AsRef<Path>, key: &SecretKey) -> Result<()> {
+        let mut content = Vec::new();
+        cookie_store::serde::json::save_incl_expired_and_nonpersistent(&self.lock(), &mut content)
+            .map_err(|e| Error::Store(format!("无法序列化 cookie: {e}")))?;
+        write_atomic(path.as_ref(), encrypt(&content, key)?.as_bytes())
+    }
+
+    // 导入手动拼接的 cookie 字符串(如 "ecs_token=..; ecs_acc=.."), 视为 url 所在域名下的 cookie
+    pub fn insert_cookie_header(&self, cookie: &str, url: &str) -> Result<()> {
+        let url = Url::parse(url).map_err(|e| Error::Config(format!("无效的 url {url}: {e}")))?;
+        let mut store = self.lock();
+        for part in cookie.split(';') {
+            let Ok(raw) = RawCookie::parse(part.trim().to_string()) else {
+                continue;
+            };
+            let mut raw = raw.into_owned();
+            raw.set_path("/");
+            store
+                .insert_raw(&raw, &url)
+                .map_err(|e| Error::Store(format!("无法导入 cookie {}: {e}", raw.name())))?;
+        }
+        Ok(())
+    }
+
+    // 所有未过期的 cookie
+    pub fn cookies(&self) -> Vec<CookieInfo> {
+        self.lock()
+            .iter_unexpired()
+            .map(|cookie| CookieInfo {
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                domain: cookie.domain().map(str::to_string),
+                path: String::from(&cookie.path),
+                expires: expiry(&cookie.expires),
+            })
+            .collect()
+    }
+
+    // ecs cookie 是否都存在且未过期
+    pub fn has_ecs_cookies(&self) -> bool {
+        let cookies = self.cookies();
+        ECS_COOKIES
+            .iter()
+            .all(|name| cookies.iter().any(|cookie| cookie.name == *name))
+    }
+
+    // ecs cookie 中最早的过期时间, 缺少 ecs cookie 或均为会话 cookie 时返回 None
+    pub fn ecs_expiry(&self) -> Option<DateTime<Local>> {
+        if !self.has_ecs_cookies() {
+            return None;
+        }
+        self.cookies()
+            .into_iter()
+            .filter(|cookie| ECS_COOKIES.contains(&cookie.name.as_str()))
+            .filter_map(|cookie| cookie.expires)
+            .min()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub(crate) fn provider(&self) -> Arc<CookieStoreMutex> {
+        self.store.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CookieStore> {
+        // 持锁期间不会 panic, 被污染时直接沿用其中的数据
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn expiry(expires: &CookieExpiration) -> Option<DateTime<Local>> {
+    match expires {
+        CookieExpiration::AtUtc(time) => DateTime::from_timestamp(time.unix_timestamp(), 0)
+            .map(|time| time.with_timezone(&Local)),
+        CookieExpiration::SessionEnd => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::temp_dir;
+
+    const URL: &str = "https://m.client.10010.com";
+
+    // cookie 的名称与值, cookies() 的顺序不固定, 这里排序后比较
+    fn pairs(jar: &CookieJar) -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = jar
+            .cookies()
+            .into_iter()
+            .map(|cookie| (cookie.name, cookie.value))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn open_without_file_is_empty() {
+        let path = temp_dir("jar-missing").join("cookies.enc");
+        let jar = CookieJar::open(&path, SecretKey::passphrase("key")).unwrap();
+
+        assert!(jar.cookies().is_empty());
+        assert_eq!(jar.path(), Some(path.as_path()));
+    }
+
+    #[test]
+    fn save_open_round_trip_keeps_session_cookies() {
+        let path = temp_dir("jar-round-trip").join("cookies.enc");
+        let key = SecretKey::passphrase("key");
+        let jar = CookieJar::new();
+        // 手动导入的 cookie 没有过期时间, 是会话 cookie
+        jar.insert_cookie_header("ecs_token=token; ecs_acc=acc", URL)
+            .unwrap();
+
+        jar.save(&path, &key).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("ecs_token"), "{content}");
+        let opened = CookieJar::open(&path, key).unwrap();
+        assert_eq!(
+            pairs(&opened),
+            [
+                ("ecs_acc".to_string(), "acc".to_string()),
+                ("ecs_token".to_string(), "token".to_string()),
+            ]
+        );
+        assert!(opened
+            .cookies()
+            .iter()
+            .all(|cookie| cookie.expires.is_none()));
+    }
+
+    #[test]
+    fn persist_writes_back_to_opened_file() {
+        let path = temp_dir("jar-persist").join("cookies.enc");
+        let jar = CookieJar::open(&path, SecretKey::passphrase("key")).unwrap();
+        jar.insert_cookie_header("ecs_token=token; ecs_acc=acc", URL)
+            .unwrap();
+
+        jar.persist().unwrap();
+
+        let opened = CookieJar::open(&path, SecretKey::passphrase("key")).unwrap();
+        assert!(opened.has_ecs_cookies());
+    }
+
+    #[test]
+    fn open_with_wrong_key_is_secret_error() {
+        let path = temp_dir("jar-wrong-key").join("cookies.enc");
+        let jar = CookieJar::new();
+        jar.insert_cookie_header("ecs_token=token; ecs_acc=acc", URL)
+            .unwrap();
+        jar.save(&path, &SecretKey::passphrase("right")).unwrap();
+
+        let err = CookieJar::open(&path, SecretKey::passphrase("wrong")).unwrap_err();
+        assert!(matches!(err, Error::Secret(_)), "{err:?}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_dir("jar-mode").join("cookies.enc");
+        CookieJar::new()
+            .save(&path, &SecretKey::passphrase("key"))
+            .unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
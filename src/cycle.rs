@@ -0,0 +1,53 @@
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+// 账期, 每月 start_day 日零点重置用量
+// 当月没有这一天时(如 31 日)在当月最后一天重置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BillingCycle {
+    pub start_day: u32,
+}
+
+impl Default for BillingCycle {
+    fn default() -> Self {
+        Self { start_day: 1 }
+    }
+}
+
+impl BillingCycle {
+    // start_day 限制在 1~31
+    pub fn new(start_day: u32) -> Self {
+        Self {
+            start_day: start_day.clamp(1, 31),
+        }
+    }
+
+    // date 所在账期的起始日期
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        let this_month = self.reset_day_in(date);
+        if date >= this_month {
+            this_month
+        } else {
+            self.reset_day_in(date - Months::new(1))
+        }
+    }
+
+    // date 之后的下一次重置日期
+    pub fn next_reset(&self, date: NaiveDate) -> NaiveDate {
+        self.reset_day_in(self.start_of(date) + Months::new(1))
+    }
+
+    // 两个日期是否属于同一账期
+    pub fn is_same_cycle(&self, a: NaiveDate, b: NaiveDate) -> bool {
+        self.start_of(a) == self.start_of(b)
+    }
+
+    // date 所在月份的重置日期
+    fn reset_day_in(&self, date: NaiveDate) -> NaiveDate {
+        let first = date.with_day(1).unwrap_or(date);
+        let last = (first + Months::new(1)).pred_opt().unwrap_or(first);
+        first
+            .with_day(self.start_day.min(last.day()))
+            .unwrap_or(first)
+    }
+}
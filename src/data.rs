@@ -1,7 +1,12 @@
-use crate::{format_duration, DATETIME_FORMAT, DEFAULT_FORMAT, DEFAULT_FORMAT_WITH_LAST};
-use aho_corasick::AhoCorasick;
-use anyhow::Result;
-use chrono::{DateTime, Local};
+use crate::amount::DataAmount;
+use crate::cycle::BillingCycle;
+use crate::delta::UsageDelta;
+use crate::error::Result;
+use crate::quota::Quota;
+use crate::tariff::Tariff;
+use crate::template::Template;
+use crate::{DEFAULT_FORMAT, DEFAULT_FORMAT_WITH_LAST};
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -12,22 +17,22 @@ pub struct ChinaUnicomData {
     pub time: DateTime<Local>,
 
     // 已用流量
-    pub sum_flow_used: f64,
+    pub sum_flow_used: DataAmount,
     // 已用定向流量
-    pub limit_flow_used: f64,
+    pub limit_flow_used: DataAmount,
     // 已用通用流量
-    pub non_limit_flow_used: f64,
+    pub non_limit_flow_used: DataAmount,
     // 已用免费流量
-    pub free_flow_used: f64,
+    pub free_flow_used: DataAmount,
     // 已用非免费流量
-    pub non_free_flow_used: f64,
+    pub non_free_flow_used: DataAmount,
 
     // 总
-    pub sum_flow: f64,
+    pub sum_flow: Quota<DataAmount>,
     // 总定向流量
-    pub limit_flow: f64,
+    pub limit_flow: Quota<DataAmount>,
     // 总通用流量
-    pub non_limit_flow: f64,
+    pub non_limit_flow: Quota<DataAmount>,
 
     // 已用通话
     pub sum_voice_used: i64,
@@ -37,306 +42,147 @@ pub struct ChinaUnicomData {
     pub non_limit_voice_used: i64,
 
     // 总通话
-    pub sum_voice: i64,
+    pub sum_voice: Quota<i64>,
     // 总定向通话
-    pub limit_voice: i64,
+    pub limit_voice: Quota<i64>,
     // 总通用通话
-    pub non_limit_voice: i64,
+    pub non_limit_voice: Quota<i64>,
+
+    // 已用短信
+    #[serde(default)]
+    pub sum_sms_used: i64,
+    // 已用定向短信
+    #[serde(default)]
+    pub limit_sms_used: i64,
+    // 已用通用短信
+    #[serde(default)]
+    pub non_limit_sms_used: i64,
+
+    // 总短信
+    #[serde(default)]
+    pub sum_sms: Quota<i64>,
+    // 总定向短信
+    #[serde(default)]
+    pub limit_sms: Quota<i64>,
+    // 总通用短信
+    #[serde(default)]
+    pub non_limit_sms: Quota<i64>,
+
+    // 是否已达到高速流量上限(封顶)而被限速
+    #[serde(default)]
+    pub throttled: bool,
+    // 限速前剩余的高速流量, 接口未返回时为 None
+    #[serde(default)]
+    pub high_speed_flow_remaining: Option<DataAmount>,
+
+    // 超出套餐的流量
+    #[serde(default)]
+    pub flow_exceed: DataAmount,
+    // 超出套餐的通话(分钟)
+    #[serde(default)]
+    pub voice_exceed: i64,
+    // 超出套餐的短信(条)
+    #[serde(default)]
+    pub sms_exceed: i64,
+
+    // 上月结转的流量, 不计入上面的流量总量
+    #[serde(default)]
+    pub carry_over_flow: DataAmount,
+    // 已用结转流量
+    #[serde(default)]
+    pub carry_over_flow_used: DataAmount,
+    // 结转流量失效日期, 有多个结转包时取最早的
+    #[serde(default)]
+    pub carry_over_flow_expiry: Option<NaiveDate>,
+
+    // 各流量包明细
+    #[serde(default)]
+    pub flow_packages: Vec<PackageUsage<DataAmount>>,
+    // 各通话包明细(分钟)
+    #[serde(default)]
+    pub voice_packages: Vec<PackageUsage<i64>>,
+    // 各短信包明细(条)
+    #[serde(default)]
+    pub sms_packages: Vec<PackageUsage<i64>>,
 }
 
-// (总量，通用总量，定向总量，总余量，通用余量，定向余量)
-pub(crate) fn parse_infinate_voice(
-    limit_total: i64,
-    non_limit_total: i64,
-    limit_used: i64,
-    non_limited_used: i64,
-) -> (String, String, String, String, String, String) {
-    if limit_total == 0 && limit_used > 0 {
-        if non_limit_total == 0 && non_limited_used > 0 {
-            return (
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-            );
-        } else {
-            return (
-                format!("{:.2}分钟", non_limit_total),
-                format!("{:.2}分钟", non_limit_total),
-                "无限".to_string(),
-                format!("{:.2}分钟", non_limit_total - non_limited_used),
-                format!("{:.2}分钟", non_limit_total - non_limited_used),
-                "无限".to_string(),
-            );
-        }
-    } else {
-        if non_limit_total == 0 && non_limited_used > 0 {
-            return (
-                "无限".to_string(),
-                "无限".to_string(),
-                format!("{:.2}分钟", limit_total),
-                "无限".to_string(),
-                "无限".to_string(),
-                format!("{:.2}分钟", limit_total - limit_used),
-            );
-        } else {
-            return (
-                format!("{:.2}分钟", limit_total + non_limit_total),
-                format!("{:.2}分钟", non_limit_total),
-                format!("{:.2}分钟", limit_total),
-                format!(
-                    "{:.2}分钟",
-                    limit_total + non_limit_total - non_limited_used - limit_used
-                ),
-                format!("{:.2}分钟", non_limit_total - non_limited_used),
-                format!("{:.2}分钟", limit_total - limit_used),
-            );
-        }
-    }
+// 单个资源包(流量包、通话包、短信包)的用量, 数量单位与所属资源一致
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageUsage<T> {
+    // 资费名称
+    pub fee_policy_name: String,
+    // 是否为定向包
+    pub limited: bool,
+    // 总量
+    pub total: Quota<T>,
+    // 已用
+    pub used: T,
+    // 剩余, 无限时为 Quota::Unlimited
+    pub remaining: Quota<T>,
+    // 生效日期
+    pub start_date: Option<NaiveDate>,
+    // 失效日期
+    pub end_date: Option<NaiveDate>,
+    // 是否为上月结转
+    pub carry_over: bool,
 }
 
-// (总量，通用总量，定向总量，总余量，通用余量，定向余量)
-pub fn parse_infinate_flow(
-    limit_total: f64,
-    non_limit_total: f64,
-    limit_used: f64,
-    non_limited_used: f64,
-) -> (String, String, String, String, String, String) {
-    if limit_total == 0.0 && limit_used > 0.0 {
-        if non_limit_total == 0.0 && non_limited_used > 0.0 {
-            return (
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-                "无限".to_string(),
-            );
-        } else {
-            return (
-                format!("{:.2}G", non_limit_total),
-                format!("{:.2}G", non_limit_total),
-                "无限".to_string(),
-                format!("{:.2}G", non_limit_total - non_limited_used),
-                format!("{:.2}G", non_limit_total - non_limited_used),
-                "无限".to_string(),
-            );
-        }
-    } else {
-        if non_limit_total == 0.0 && non_limited_used > 0.0 {
-            return (
-                "无限".to_string(),
-                "无限".to_string(),
-                format!("{:.2}G", limit_total),
-                "无限".to_string(),
-                "无限".to_string(),
-                format!("{:.2}G", limit_total - limit_used),
-            );
-        } else {
-            return (
-                format!("{:.2}G", limit_total + non_limit_total),
-                format!("{:.2}G", non_limit_total),
-                format!("{:.2}G", limit_total),
-                format!(
-                    "{:.2}G",
-                    limit_total + non_limit_total - non_limited_used - limit_used
-                ),
-                format!("{:.2}G", non_limit_total - non_limited_used),
-                format!("{:.2}G", limit_total - limit_used),
-            );
+impl ChinaUnicomData {
+    // 剩余的结转流量
+    pub fn carry_over_flow_remaining(&self) -> DataAmount {
+        (self.carry_over_flow - self.carry_over_flow_used).max(DataAmount::ZERO)
+    }
+
+    // 距结转流量失效还有几天, 没有结转流量时返回 None
+    pub fn carry_over_flow_days_left(&self) -> Option<i64> {
+        if self.carry_over_flow_remaining().is_zero() {
+            return None;
         }
+        let expiry = self.carry_over_flow_expiry?;
+        Some((expiry - self.time.date_naive()).num_days().max(0))
     }
-}
 
-impl ChinaUnicomData {
-    pub fn format(&self, fmt: &str) -> Result<String> {
-        let patterns = &[
-            "[时间]",
-            "[流量总量]",
-            "[流量定向总量]",
-            "[流量通用总量]",
-            "[流量总用量]",
-            "[流量免费用量]",
-            "[流量收费用量]",
-            "[流量定向用量]",
-            "[流量通用用量]",
-            "[通话总用量]",
-            "[通话定向用量]",
-            "[通话通用用量]",
-            "[通话总量]",
-            "[通话定向总量]",
-            "[通话通用总量]",
-            "[流量总余量]",
-            "[流量定向余量]",
-            "[流量通用余量]",
-            "[通话总余量]",
-            "[通话定向余量]",
-            "[通话通用余量]",
-            "[套餐名称]",
-        ];
+    // 流量、通话、短信是否有任一超出套餐
+    pub fn is_over_quota(&self) -> bool {
+        self.flow_exceed > DataAmount::ZERO || self.voice_exceed > 0 || self.sms_exceed > 0
+    }
 
-        let (
-            sum_voice,
-            limit_voice,
-            non_limit_voice,
-            sum_voice_left,
-            limit_voice_left,
-            non_limit_voice_left,
-        ) = parse_infinate_voice(
-            self.limit_voice,
-            self.non_limit_voice,
-            self.limit_voice_used,
-            self.non_limit_voice_used,
-        );
-        let (
-            sum_flow,
-            non_limit_flow,
-            limit_flow,
-            sum_flow_left,
-            non_limit_flow_left,
-            limit_flow_left,
-        ) = parse_infinate_flow(
-            self.limit_flow,
-            self.non_limit_flow,
-            self.limit_flow_used,
-            self.non_limit_flow_used,
-        );
-        let replace_with = &[
-            self.time.format(DATETIME_FORMAT).to_string(),
-            sum_flow,
-            limit_flow,
-            non_limit_flow,
-            format!("{:.2}G", self.sum_flow_used),
-            format!("{:.2}G", self.free_flow_used),
-            format!("{:.2}G", self.non_free_flow_used),
-            format!("{:.2}G", self.limit_flow_used),
-            format!("{:.2}G", self.non_limit_flow_used),
-            format!("{:.2}分钟", self.sum_voice_used),
-            format!("{:.2}分钟", self.limit_voice_used),
-            format!("{:.2}分钟", self.non_limit_voice_used),
-            sum_voice,
-            limit_voice,
-            non_limit_voice,
-            sum_flow_left,
-            limit_flow_left,
-            non_limit_flow_left,
-            sum_voice_left,
-            limit_voice_left,
-            non_limit_voice_left,
-            self.package_name.clone(),
-        ];
+    // 按 tariff 估算的超出费用(元)
+    pub fn overage_cost(&self, tariff: &Tariff) -> f64 {
+        tariff.flow_cost(self.flow_exceed)
+            + tariff.voice_cost(self.voice_exceed)
+            + tariff.sms_cost(self.sms_exceed)
+    }
+
+    // 每次调用都会重新编译模板, 多次使用同一模板时请使用 Template
+    pub fn format(&self, fmt: &str) -> Result<String> {
+        Template::compile(fmt)?.render(self)
+    }
 
-        let ac = AhoCorasick::new(patterns).unwrap();
-        let mut result = vec![];
-        ac.try_stream_replace_all(fmt.as_bytes(), &mut result, replace_with)?;
-        Ok(String::from_utf8(result).unwrap())
+    // [超出费用] 按 tariff 估算
+    pub fn format_with_tariff(&self, fmt: &str, tariff: &Tariff) -> Result<String> {
+        Template::compile(fmt)?.tariff(*tariff).render(self)
     }
 
     pub fn format_with_last(&self, fmt: &str, last: &Self) -> Result<String> {
-        let duration = self.time - last.time;
-        let duration_str = format_duration(duration);
-        let sum_flow_used = (self.sum_flow_used - last.sum_flow_used).max(0.0);
-        let free_flow_used = (self.free_flow_used - last.free_flow_used).max(0.0);
-        let non_free_flow_used = (self.non_free_flow_used - last.non_free_flow_used).max(0.0);
-        let sum_voice_used = (self.sum_voice_used - last.sum_voice_used).max(0);
-        let limit_voice_used = (self.limit_voice_used - last.limit_voice_used).max(0);
-        let non_limit_voice_used = (self.non_limit_voice_used - last.non_limit_voice_used).max(0);
+        Template::compile(fmt)?.render_with_last(self, last)
+    }
 
-        let patterns = &[
-            "[区间时长]",
-            "[区间流量总用量]",
-            "[区间流量免费用量]",
-            "[区间流量收费用量]",
-            "[区间通话总用量]",
-            "[区间通话定向用量]",
-            "[区间通话通用用量]",
-            "[时间]",
-            "[流量总量]",
-            "[流量定向总量]",
-            "[流量通用总量]",
-            "[流量总用量]",
-            "[流量免费用量]",
-            "[流量收费用量]",
-            "[流量定向用量]",
-            "[流量通用用量]",
-            "[通话总用量]",
-            "[通话定向用量]",
-            "[通话通用用量]",
-            "[通话总量]",
-            "[通话定向总量]",
-            "[通话通用总量]",
-            "[流量总余量]",
-            "[流量定向余量]",
-            "[流量通用余量]",
-            "[通话总余量]",
-            "[通话定向余量]",
-            "[通话通用余量]",
-            "[套餐名称]",
-        ];
-        let (
-            sum_voice,
-            limit_voice,
-            non_limit_voice,
-            sum_voice_left,
-            limit_voice_left,
-            non_limit_voice_left,
-        ) = parse_infinate_voice(
-            self.limit_voice,
-            self.non_limit_voice,
-            self.limit_voice_used,
-            self.non_limit_voice_used,
-        );
-        let (
-            sum_flow,
-            non_limit_flow,
-            limit_flow,
-            sum_flow_left,
-            non_limit_flow_left,
-            limit_flow_left,
-        ) = parse_infinate_flow(
-            self.limit_flow,
-            self.non_limit_flow,
-            self.limit_flow_used,
-            self.non_limit_flow_used,
-        );
-        let replace_with = &[
-            duration_str,
-            format!("{:.2}G", sum_flow_used),
-            format!("{:.2}G", free_flow_used),
-            format!("{:.2}G", non_free_flow_used),
-            format!("{:.2}分钟", sum_voice_used),
-            format!("{:.2}分钟", limit_voice_used),
-            format!("{:.2}分钟", non_limit_voice_used),
-            self.time.format(DATETIME_FORMAT).to_string(),
-            sum_flow,
-            limit_flow,
-            non_limit_flow,
-            format!("{:.2}G", self.sum_flow_used),
-            format!("{:.2}G", self.free_flow_used),
-            format!("{:.2}G", self.non_free_flow_used),
-            format!("{:.2}G", self.limit_flow_used),
-            format!("{:.2}G", self.non_limit_flow_used),
-            format!("{:.2}分钟", self.sum_voice_used),
-            format!("{:.2}分钟", self.limit_voice_used),
-            format!("{:.2}分钟", self.non_limit_voice_used),
-            sum_voice,
-            limit_voice,
-            non_limit_voice,
-            sum_flow_left,
-            limit_flow_left,
-            non_limit_flow_left,
-            sum_voice_left,
-            limit_voice_left,
-            non_limit_voice_left,
-            self.package_name.clone(),
-        ];
+    // 账期不是每月 1 日重置时, 按 cycle 计算区间用量
+    pub fn format_with_last_in_cycle(
+        &self,
+        fmt: &str,
+        last: &Self,
+        cycle: BillingCycle,
+    ) -> Result<String> {
+        Template::compile(fmt)?
+            .cycle(cycle)
+            .render_with_last(self, last)
+    }
 
-        let ac = AhoCorasick::new(patterns).unwrap();
-        let mut result = vec![];
-        ac.try_stream_replace_all(fmt.as_bytes(), &mut result, replace_with)?;
-        Ok(String::from_utf8(result).unwrap())
+    // 同时使用本次查询与区间用量的占位符
+    pub fn format_with_delta(&self, fmt: &str, delta: &UsageDelta) -> Result<String> {
+        Template::compile(fmt)?.render_with_delta(self, delta)
     }
 
     pub fn format_default(&self) -> Result<String> {
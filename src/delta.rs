@@ -0,0 +1,371 @@
+use std::ops::Sub;
+
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::amount::DataAmount;
+use crate::cycle::BillingCycle;
+use crate::data::{ChinaUnicomData, PackageUsage};
+use crate::locale::{Locale, Word};
+use crate::quota::Quantity;
+
+// 两次查询之间的用量变化, 由 `current - &last` 得到
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageDelta {
+    // 上次查询时间
+    pub start: DateTime<Local>,
+    // 本次查询时间
+    pub end: DateTime<Local>,
+    // 区间时长, 序列化为秒数
+    #[serde(with = "duration_secs")]
+    pub duration: TimeDelta,
+    // 区间内账期是否已重置, 重置时各用量只包含重置之后的部分
+    #[serde(default)]
+    pub cycle_reset: bool,
+    // 区间内限速状态的变化
+    #[serde(default)]
+    pub throttle_change: Option<ThrottleChange>,
+
+    // 区间内已用流量
+    pub sum_flow_used: DataAmount,
+    // 区间内已用定向流量
+    pub limit_flow_used: DataAmount,
+    // 区间内已用通用流量
+    pub non_limit_flow_used: DataAmount,
+    // 区间内已用免费流量
+    pub free_flow_used: DataAmount,
+    // 区间内已用非免费流量
+    pub non_free_flow_used: DataAmount,
+
+    // 区间内已用结转流量
+    #[serde(default)]
+    pub carry_over_flow_used: DataAmount,
+
+    // 区间内新增的超出流量
+    #[serde(default)]
+    pub flow_exceed: DataAmount,
+    // 区间内新增的超出通话
+    #[serde(default)]
+    pub voice_exceed: i64,
+    // 区间内新增的超出短信
+    #[serde(default)]
+    pub sms_exceed: i64,
+
+    // 区间内已用通话
+    pub sum_voice_used: i64,
+    // 区间内已用定向通话
+    pub limit_voice_used: i64,
+    // 区间内已用通用通话
+    pub non_limit_voice_used: i64,
+
+    // 区间内已用短信
+    pub sum_sms_used: i64,
+    // 区间内已用定向短信
+    pub limit_sms_used: i64,
+    // 区间内已用通用短信
+    pub non_limit_sms_used: i64,
+
+    // 各流量包在区间内的用量
+    pub flow_packages: Vec<PackageDelta<DataAmount>>,
+    // 各通话包在区间内的用量
+    pub voice_packages: Vec<PackageDelta<i64>>,
+    // 各短信包在区间内的用量
+    pub sms_packages: Vec<PackageDelta<i64>>,
+}
+
+// 限速状态的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleChange {
+    // 达到高速流量上限, 开始限速
+    Throttled,
+    // 解除限速(如账期重置或加购流量)
+    Restored,
+}
+
+impl ThrottleChange {
+    fn between(last: &ChinaUnicomData, current: &ChinaUnicomData) -> Option<Self> {
+        match (last.throttled, current.throttled) {
+            (false, true) => Some(Self::Throttled),
+            (true, false) => Some(Self::Restored),
+            _ => None,
+        }
+    }
+
+    // 按 locale 输出的状态文本, 与模板中 [区间限速变化] 一致
+    pub fn as_str(self, locale: Locale) -> &'static str {
+        locale.word(match self {
+            Self::Throttled => Word::Throttled,
+            Self::Restored => Word::Restored,
+        })
+    }
+}
+
+// 单个资源包在区间内的用量
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageDelta<T> {
+    // 资费名称
+    pub fee_policy_name: String,
+    // 是否为定向包
+    pub limited: bool,
+    // 区间内用量
+    pub used: T,
+}
+
+// 账期重置后的区间用量应为 重置后的用量 + 上次查询到重置时刻之间的用量,
+// 但接口只返回当前账期的累计用量, 后者无法得知也无法估算, 不计入区间用量(结果可能偏小)
+// 重置时该项用量减少则取重置后的用量; 未减少说明接口尚未清零, 与未重置时一样相减
+// 未重置时用量只增不减, 个别字段减少(如接口修正数据)时该字段按 0 处理
+fn used_between<T: Quantity>(current: T, last: T, cycle_reset: bool) -> T {
+    if cycle_reset && current < last {
+        return current;
+    }
+    let used = current - last;
+    if used > T::default() {
+        used
+    } else {
+        T::default()
+    }
+}
+
+// 跨过账期起始日时, 任一用量减少或套餐总量变化(新账期重新下发套餐)视为重置,
+// 两者都没有说明接口尚未清零, 仍按同一账期计算
+// 未跨过起始日时, 接口清零可能滞后到新账期内: 上次查询已在本账期内但尚未清零,
+// 用量高于本次查询, 因此用量减少同样视为重置
+fn is_cycle_reset(last: &ChinaUnicomData, current: &ChinaUnicomData, cycle: BillingCycle) -> bool {
+    let used_dropped = current.sum_flow_used < last.sum_flow_used
+        || current.limit_flow_used < last.limit_flow_used
+        || current.non_limit_flow_used < last.non_limit_flow_used
+        || current.sum_voice_used < last.sum_voice_used
+        || current.sum_sms_used < last.sum_sms_used;
+    if cycle.is_same_cycle(last.time.date_naive(), current.time.date_naive()) {
+        return used_dropped;
+    }
+    let totals_changed = current.sum_flow != last.sum_flow
+        || current.sum_voice != last.sum_voice
+        || current.sum_sms != last.sum_sms;
+    used_dropped || totals_changed
+}
+
+// 按资费名称、是否定向与生效日期匹配上次的资源包, 上次没有的包(如新订购)全部计入区间用量
+fn package_deltas<T: Quantity>(
+    current: &[PackageUsage<T>],
+    last: &[PackageUsage<T>],
+    cycle_reset: bool,
+) -> Vec<PackageDelta<T>> {
+    let key = |package: &PackageUsage<T>| -> (String, bool, Option<NaiveDate>) {
+        (
+            package.fee_policy_name.clone(),
+            package.limited,
+            package.start_date,
+        )
+    };
+    let mut matched = vec![false; last.len()];
+
+    current
+        .iter()
+        .map(|package| {
+            let last_package = last
+                .iter()
+                .enumerate()
+                .find(|(i, last_package)| !matched[*i] && key(last_package) == key(package));
+            let used = match last_package {
+                Some((i, last_package)) => {
+                    matched[i] = true;
+                    used_between(package.used, last_package.used, cycle_reset)
+                }
+                None => package.used,
+            };
+            PackageDelta {
+                fee_policy_name: package.fee_policy_name.clone(),
+                limited: package.limited,
+                used,
+            }
+        })
+        .collect()
+}
+
+impl UsageDelta {
+    // last 之后到 current 的用量, 账期每月 1 日重置
+    pub fn between(last: &ChinaUnicomData, current: &ChinaUnicomData) -> Self {
+        Self::between_in_cycle(last, current, BillingCycle::default())
+    }
+
+    // last 之后到 current 的用量, 按 cycle 判断区间内账期是否重置
+    pub fn between_in_cycle(
+        last: &ChinaUnicomData,
+        current: &ChinaUnicomData,
+        cycle: BillingCycle,
+    ) -> Self {
+        let reset = is_cycle_reset(last, current, cycle);
+        Self {
+            start: last.time,
+            end: current.time,
+            duration: current.time - last.time,
+            cycle_reset: reset,
+            throttle_change: ThrottleChange::between(last, current),
+            sum_flow_used: used_between(current.sum_flow_used, last.sum_flow_used, reset),
+            limit_flow_used: used_between(current.limit_flow_used, last.limit_flow_used, reset),
+            non_limit_flow_used: used_between(
+                current.non_limit_flow_used,
+                last.non_limit_flow_used,
+                reset,
+            ),
+            free_flow_used: used_between(current.free_flow_used, last.free_flow_used, reset),
+            non_free_flow_used: used_between(
+                current.non_free_flow_used,
+                last.non_free_flow_used,
+                reset,
+            ),
+            carry_over_flow_used: used_between(
+                current.carry_over_flow_used,
+                last.carry_over_flow_used,
+                reset,
+            ),
+            flow_exceed: used_between(current.flow_exceed, last.flow_exceed, reset),
+            voice_exceed: used_between(current.voice_exceed, last.voice_exceed, reset),
+            sms_exceed: used_between(current.sms_exceed, last.sms_exceed, reset),
+            sum_voice_used: used_between(current.sum_voice_used, last.sum_voice_used, reset),
+            limit_voice_used: used_between(current.limit_voice_used, last.limit_voice_used, reset),
+            non_limit_voice_used: used_between(
+                current.non_limit_voice_used,
+                last.non_limit_voice_used,
+                reset,
+            ),
+            sum_sms_used: used_between(current.sum_sms_used, last.sum_sms_used, reset),
+            limit_sms_used: used_between(current.limit_sms_used, last.limit_sms_used, reset),
+            non_limit_sms_used: used_between(
+                current.non_limit_sms_used,
+                last.non_limit_sms_used,
+                reset,
+            ),
+            flow_packages: package_deltas(&current.flow_packages, &last.flow_packages, reset),
+            voice_packages: package_deltas(&current.voice_packages, &last.voice_packages, reset),
+            sms_packages: package_deltas(&current.sms_packages, &last.sms_packages, reset),
+        }
+    }
+
+    // 区间内是否没有任何用量
+    pub fn is_empty(&self) -> bool {
+        self.sum_flow_used.is_zero()
+            && self.limit_flow_used.is_zero()
+            && self.non_limit_flow_used.is_zero()
+            && self.sum_voice_used == 0
+            && self.sum_sms_used == 0
+    }
+}
+
+impl Sub<&ChinaUnicomData> for &ChinaUnicomData {
+    type Output = UsageDelta;
+
+    fn sub(self, last: &ChinaUnicomData) -> UsageDelta {
+        UsageDelta::between(last, self)
+    }
+}
+
+impl Sub<&ChinaUnicomData> for ChinaUnicomData {
+    type Output = UsageDelta;
+
+    fn sub(self, last: &ChinaUnicomData) -> UsageDelta {
+        UsageDelta::between(last, &self)
+    }
+}
+
+// TimeDelta 以秒数序列化
+mod duration_secs {
+    use chrono::TimeDelta;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        duration: &TimeDelta,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<TimeDelta, D::Error> {
+        let secs = i64::deserialize(deserializer)?;
+        TimeDelta::try_seconds(secs).ok_or_else(|| D::Error::custom("区间时长超出范围"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::quota::Quota;
+
+    fn snapshot(day: u32, hour: u32, flow_used_gb: f64) -> ChinaUnicomData {
+        ChinaUnicomData {
+            time: Local.with_ymd_and_hms(2024, 9, day, hour, 0, 0).unwrap(),
+            sum_flow: Quota::Limited(DataAmount::from_gb(10.0)),
+            sum_flow_used: DataAmount::from_gb(flow_used_gb),
+            non_limit_flow_used: DataAmount::from_gb(flow_used_gb),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reset_across_cycle_boundary() {
+        let last = snapshot(14, 20, 8.0);
+        let current = snapshot(15, 8, 0.5);
+
+        let delta = UsageDelta::between_in_cycle(&last, &current, BillingCycle::new(15));
+
+        assert!(delta.cycle_reset);
+        assert_eq!(delta.sum_flow_used, DataAmount::from_gb(0.5));
+        assert_eq!(delta.non_limit_flow_used, DataAmount::from_gb(0.5));
+    }
+
+    #[test]
+    fn lagged_reset_within_new_cycle() {
+        // 上次查询已在新账期内, 但接口尚未清零, 仍是上个账期的用量
+        let last = snapshot(15, 1, 8.0);
+        let current = snapshot(15, 8, 0.5);
+
+        let delta = UsageDelta::between_in_cycle(&last, &current, BillingCycle::new(15));
+
+        assert!(delta.cycle_reset);
+        assert_eq!(delta.sum_flow_used, DataAmount::from_gb(0.5));
+    }
+
+    #[test]
+    fn no_reset_within_one_cycle() {
+        let last = snapshot(16, 8, 1.0);
+        let current = snapshot(20, 8, 1.5);
+
+        let delta = UsageDelta::between_in_cycle(&last, &current, BillingCycle::new(15));
+
+        assert!(!delta.cycle_reset);
+        assert_eq!(delta.sum_flow_used, DataAmount::from_gb(0.5));
+    }
+
+    #[test]
+    fn crossing_without_drop_or_new_totals_is_not_reset() {
+        // 跨过起始日但接口尚未清零, 按同一账期相减
+        let last = snapshot(14, 20, 8.0);
+        let current = snapshot(15, 1, 8.25);
+
+        let delta = UsageDelta::between_in_cycle(&last, &current, BillingCycle::new(15));
+
+        assert!(!delta.cycle_reset);
+        assert_eq!(delta.sum_flow_used, DataAmount::from_gb(0.25));
+    }
+
+    #[test]
+    fn throttle_change_uses_locale_words() {
+        let last = snapshot(16, 8, 1.0);
+        let mut current = snapshot(20, 8, 1.5);
+        current.throttled = true;
+
+        let change = (&current - &last).throttle_change.unwrap();
+
+        assert_eq!(change, ThrottleChange::Throttled);
+        assert_eq!(change.as_str(Locale::ZhCn), "已限速");
+        assert_eq!(change.as_str(Locale::En), "throttled");
+        assert_eq!(ThrottleChange::Restored.as_str(Locale::En), "restored");
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::{self, Debug};
+
+use thiserror::Error;
+
+use crate::secret::REDACTED;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    // 网络错误、超时、无法读取响应等
+    #[error("请求失败: {0}")]
+    Transport(#[from] reqwest::Error),
+
+    // 接口返回非 2xx 状态码, 5xx 多为联通服务端临时故障, 可稍后重试
+    #[error("接口返回 HTTP {status}")]
+    Http { status: u16, body: ResponseBody },
+
+    // cookie 或 token_online 已失效, 需要重新登录
+    #[error("登录状态已失效({code}): {desc}")]
+    SessionExpired { code: String, desc: String },
+
+    // 登录被拒绝, 如验证码或密码错误
+    #[error("登录失败({code}): {desc}")]
+    LoginFailed { code: String, desc: String },
+
+    // 响应中没有返回所需的 cookie
+    #[error("响应中缺少 cookie: {0}")]
+    MissingCookie(String),
+
+    // 响应无法识别, 附带原始响应体便于排查
+    #[error("无法识别的响应: {reason}")]
+    UnexpectedResponse { reason: String, body: ResponseBody },
+
+    // 响应中某个字段无法解析
+    #[error("无法解析字段 {field}: {reason}")]
+    Parse { field: String, reason: String },
+
+    // 格式化输出失败
+    #[error("格式化失败: {0}")]
+    Format(String),
+
+    // 读写本地文件失败
+    #[error("读写文件失败: {0}")]
+    Io(#[from] std::io::Error),
+
+    // 凭据无法保存或读取
+    #[error("凭据存储失败: {0}")]
+    Store(String),
+
+    // 加密文件无法解密或密钥无效
+    #[error("加密文件错误: {0}")]
+    Secret(String),
+
+    // 客户端配置有误
+    #[error("配置无效: {0}")]
+    Config(String),
+}
+
+impl Error {
+    // 是否需要重新登录(刷新 cookie)后再重试
+    pub fn is_session_expired(&self) -> bool {
+        matches!(self, Self::SessionExpired { .. })
+    }
+
+    pub(crate) fn parse(field: impl Into<String>, reason: impl ToString) -> Self {
+        Self::Parse {
+            field: field.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub(crate) fn unexpected(reason: impl ToString, body: impl Into<String>) -> Self {
+        Self::UnexpectedResponse {
+            reason: reason.to_string(),
+            body: ResponseBody(body.into()),
+        }
+    }
+}
+
+// 错误中附带的原始响应体, 可能含有手机号、token 等信息, Debug 输出只显示长度
+#[derive(Clone, PartialEq, Eq)]
+pub struct ResponseBody(String);
+
+impl ResponseBody {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl From<String> for ResponseBody {
+    fn from(body: String) -> Self {
+        Self(body)
+    }
+}
+
+impl Debug for ResponseBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseBody")
+            .field("len", &self.0.len())
+            .field("content", &REDACTED)
+            .finish()
+    }
+}
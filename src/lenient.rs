@@ -0,0 +1,268 @@
+// 联通接口返回的字段类型并不稳定: 同一字段可能是字符串、数字或 null,
+// 这里的反序列化函数接受这些已知写法, null 与空字符串视为默认值,
+// 其余无法识别的值返回错误, 避免把接口变化当作 0 用量
+
+use serde::{de::Error, Deserialize, Deserializer};
+use serde_json::Value;
+
+pub(crate) fn string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(opt_string(deserializer)?.unwrap_or_default())
+}
+
+pub(crate) fn opt_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(s) => Ok(Some(s)),
+        Value::Number(n) => Ok(Some(n.to_string())),
+        Value::Bool(b) => Ok(Some(b.to_string())),
+        other => Err(D::Error::custom(format!("需要字符串, 实际为 {other}"))),
+    }
+}
+
+pub(crate) fn f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(0.0),
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| D::Error::custom(format!("无法识别的数字 {n}"))),
+        Value::String(s) if s.trim().is_empty() => Ok(0.0),
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| D::Error::custom(format!("无法识别的数字 {s:?}"))),
+        other => Err(D::Error::custom(format!("需要数字, 实际为 {other}"))),
+    }
+}
+
+pub(crate) fn i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(0),
+        Value::Number(n) => n
+            .as_i64()
+            .or_else(|| n.as_f64().map(|f| f as i64))
+            .ok_or_else(|| D::Error::custom(format!("无法识别的整数 {n}"))),
+        Value::String(s) if s.trim().is_empty() => Ok(0),
+        Value::String(s) => {
+            let trimmed = s.trim();
+            trimmed
+                .parse()
+                .or_else(|_| trimmed.parse::<f64>().map(|f| f as i64))
+                .map_err(|_| D::Error::custom(format!("无法识别的整数 {s:?}")))
+        }
+        other => Err(D::Error::custom(format!("需要整数, 实际为 {other}"))),
+    }
+}
+
+// 布尔值可能写作 true、1、"1"、"Y" 等, null 与空字符串视为 false
+pub(crate) fn bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(false),
+        Value::Bool(b) => Ok(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(D::Error::custom(format!("无法识别的布尔值 {n}"))),
+        },
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "" | "0" | "false" | "n" | "no" => Ok(false),
+            "1" | "true" | "y" | "yes" => Ok(true),
+            _ => Err(D::Error::custom(format!("无法识别的布尔值 {s:?}"))),
+        },
+        other => Err(D::Error::custom(format!("需要布尔值, 实际为 {other}"))),
+    }
+}
+
+// null 与空字符串视为空数组, 任一元素无法解析时返回错误
+pub(crate) fn vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(Vec::new()),
+        Value::String(s) if s.trim().is_empty() => Ok(Vec::new()),
+        Value::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                serde_json::from_value(item)
+                    .map_err(|e| D::Error::custom(format!("第 {i} 个元素: {e}")))
+            })
+            .collect(),
+        other => Err(D::Error::custom(format!("需要数组, 实际为 {other}"))),
+    }
+}
+
+// null 与空字符串视为默认值, 对象无法解析时返回错误
+pub(crate) fn object<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(T::default()),
+        Value::String(s) if s.trim().is_empty() => Ok(T::default()),
+        value @ Value::Object(_) => serde_json::from_value(value).map_err(D::Error::custom),
+        other => Err(D::Error::custom(format!("需要对象, 实际为 {other}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default)]
+    struct Fields {
+        #[serde(deserialize_with = "super::string")]
+        string: String,
+        #[serde(deserialize_with = "super::opt_string")]
+        opt_string: Option<String>,
+        #[serde(deserialize_with = "super::f64")]
+        f64: f64,
+        #[serde(deserialize_with = "super::i64")]
+        i64: i64,
+        #[serde(deserialize_with = "super::bool")]
+        bool: bool,
+        #[serde(deserialize_with = "super::vec")]
+        vec: Vec<Item>,
+        #[serde(deserialize_with = "super::object")]
+        object: Item,
+    }
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    struct Item {
+        id: i64,
+    }
+
+    fn parse(field: &str, value: Value) -> serde_json::Result<Fields> {
+        serde_json::from_value(json!({ field: value }))
+    }
+
+    fn parse_err(field: &str, value: Value) {
+        let result = parse(field, value.clone());
+        assert!(result.is_err(), "{field} = {value}: {result:?}");
+    }
+
+    #[test]
+    fn missing_fields_are_default() {
+        let fields: Fields = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(fields.string, "");
+        assert_eq!(fields.opt_string, None);
+        assert_eq!(fields.f64, 0.0);
+        assert_eq!(fields.i64, 0);
+        assert!(!fields.bool);
+        assert!(fields.vec.is_empty());
+        assert_eq!(fields.object, Item::default());
+    }
+
+    #[test]
+    fn null_is_default() {
+        for field in ["string", "f64", "i64", "bool", "vec", "object"] {
+            parse(field, Value::Null).unwrap();
+        }
+        assert_eq!(parse("opt_string", Value::Null).unwrap().opt_string, None);
+        assert_eq!(parse("string", Value::Null).unwrap().string, "");
+    }
+
+    #[test]
+    fn strings_accept_scalars() {
+        assert_eq!(parse("string", json!("a")).unwrap().string, "a");
+        assert_eq!(parse("string", json!(12)).unwrap().string, "12");
+        assert_eq!(parse("string", json!(1.5)).unwrap().string, "1.5");
+        assert_eq!(parse("string", json!(true)).unwrap().string, "true");
+        assert_eq!(
+            parse("opt_string", json!(0)).unwrap().opt_string.as_deref(),
+            Some("0")
+        );
+    }
+
+    #[test]
+    fn strings_reject_arrays_and_objects() {
+        for field in ["string", "opt_string"] {
+            parse_err(field, json!(["a"]));
+            parse_err(field, json!({ "a": 1 }));
+        }
+    }
+
+    #[test]
+    fn numbers_accept_numbers_and_numeric_strings() {
+        assert_eq!(parse("f64", json!(1.5)).unwrap().f64, 1.5);
+        assert_eq!(parse("f64", json!(" 2.5 ")).unwrap().f64, 2.5);
+        assert_eq!(parse("f64", json!("")).unwrap().f64, 0.0);
+        assert_eq!(parse("i64", json!(3)).unwrap().i64, 3);
+        assert_eq!(parse("i64", json!("4")).unwrap().i64, 4);
+        assert_eq!(parse("i64", json!("5.0")).unwrap().i64, 5);
+        assert_eq!(parse("i64", json!(6.0)).unwrap().i64, 6);
+        assert_eq!(parse("i64", json!(" ")).unwrap().i64, 0);
+    }
+
+    #[test]
+    fn numbers_reject_unknown_values() {
+        for field in ["f64", "i64"] {
+            parse_err(field, json!("abc"));
+            parse_err(field, json!(true));
+            parse_err(field, json!([1]));
+            parse_err(field, json!({ "value": 1 }));
+        }
+    }
+
+    #[test]
+    fn bools_accept_known_spellings() {
+        for value in [
+            json!(true),
+            json!(1),
+            json!("1"),
+            json!("true"),
+            json!("Y"),
+            json!("yes"),
+        ] {
+            assert!(parse("bool", value.clone()).unwrap().bool, "{value}");
+        }
+        for value in [
+            json!(false),
+            json!(0),
+            json!("0"),
+            json!("false"),
+            json!("N"),
+            json!(""),
+        ] {
+            assert!(!parse("bool", value.clone()).unwrap().bool, "{value}");
+        }
+    }
+
+    #[test]
+    fn bools_reject_unknown_values() {
+        parse_err("bool", json!("maybe"));
+        parse_err("bool", json!(2));
+        parse_err("bool", json!([true]));
+        parse_err("bool", json!({ "value": true }));
+    }
+
+    #[test]
+    fn vec_parses_every_element() {
+        let fields = parse("vec", json!([{ "id": 1 }, { "id": 2 }])).unwrap();
+        assert_eq!(fields.vec, [Item { id: 1 }, Item { id: 2 }]);
+        assert!(parse("vec", json!("")).unwrap().vec.is_empty());
+
+        parse_err("vec", json!([{ "id": 1 }, { "id": "x" }]));
+        parse_err("vec", json!({ "id": 1 }));
+        parse_err("vec", json!("x"));
+    }
+
+    #[test]
+    fn object_parses_or_fails() {
+        assert_eq!(
+            parse("object", json!({ "id": 1 })).unwrap().object,
+            Item { id: 1 }
+        );
+        assert_eq!(parse("object", json!("")).unwrap().object, Item::default());
+
+        parse_err("object", json!({ "id": "x" }));
+        parse_err("object", json!([{ "id": 1 }]));
+        parse_err("object", json!(1));
+    }
+}
@@ -1,31 +1,61 @@
-use std::cell::LazyCell;
-
 use chrono::TimeDelta;
-use reqwest::Client;
 
+pub mod amount;
+pub mod client;
+pub mod cookie_jar;
+pub mod cycle;
 pub mod data;
-pub mod query;
+pub mod delta;
+pub mod error;
+mod lenient;
+pub mod locale;
+pub mod login;
 pub mod online;
+pub mod query;
+pub mod quota;
+pub mod secret;
+pub mod session;
+pub mod store;
+pub mod tariff;
+pub mod template;
 
-pub const CLIENT: LazyCell<Client> = LazyCell::new(|| Client::new());
+pub use amount::DataAmount;
+pub use client::{ChinaUnicomClient, ChinaUnicomClientBuilder};
+pub use cookie_jar::CookieJar;
+pub use cycle::BillingCycle;
+pub use delta::{PackageDelta, ThrottleChange, UsageDelta};
+pub use error::{Error, ResponseBody, Result};
+pub use locale::Locale;
+pub use quota::{Quantity, Quota};
+pub use secret::{EncryptedCredentialStore, SecretKey};
+pub use session::Session;
+pub use store::{CredentialStore, Credentials, FileCredentialStore};
+pub use tariff::Tariff;
+pub use template::{ProgressBar, Template};
 
 // 定义一个全局的格式化字符串
 const DEFAULT_FORMAT: &str = "时间: [时间]
 流量:
     * 总量:
-        - 总流量: [流量总量]
-        - 定向流量: [流量定向总量]
+        - 总流量: [流量总量][#非零 流量定向总量]
+        - 定向流量: [流量定向总量][/非零]
         - 通用流量: [流量通用总量]
     * 用量:
-        - 总流量: [流量总用量]
-        - 定向流量: [流量定向用量]
+        - 总流量: [流量总用量][#非零 流量定向总量]
+        - 定向流量: [流量定向用量][/非零]
         - 通用流量: [流量通用用量]
         - 免费流量: [流量免费用量]
         - 收费流量: [流量收费用量]
     * 余量:
-        - 总流量: [流量总余量]
-        - 定向流量: [流量定向余量]
-        - 通用流量: [流量通用余量]
+        - 总流量: [流量总余量][#非零 流量定向总量]
+        - 定向流量: [流量定向余量][/非零]
+        - 通用流量: [流量通用余量][#非零 结转流量总量]
+    * 结转:
+        - 剩余: [结转流量余量]
+        - 到期: [结转流量到期][/非零]
+    * 限速:
+        - 状态: [限速状态]
+        - 高速流量余量: [高速流量余量][#非零 通话总量]
 通话:
     * 总量:
         - 总通话时长: [通话总量]
@@ -38,7 +68,25 @@ const DEFAULT_FORMAT: &str = "时间: [时间]
     * 余量:
         - 总通话时长: [通话总余量]
         - 定向通话时长: [通话定向余量]
-        - 通用通话时长: [通话通用余量]";
+        - 通用通话时长: [通话通用余量][/非零][#非零 短信总量]
+短信:
+    * 总量:
+        - 总短信: [短信总量]
+        - 定向短信: [短信定向总量]
+        - 通用短信: [短信通用总量]
+    * 用量:
+        - 总短信: [短信总用量]
+        - 定向短信: [短信定向用量]
+        - 通用短信: [短信通用用量]
+    * 余量:
+        - 总短信: [短信总余量]
+        - 定向短信: [短信定向余量]
+        - 通用短信: [短信通用余量][/非零][#非零 超出费用]
+超出:
+    - 流量: [流量超出]
+    - 通话: [通话超出]
+    - 短信: [短信超出]
+    - 预估费用: [超出费用][/非零]";
 
 // 定义一个全局纯区间信息的格式化字符串
 const DEFAULT_FORMAT_WITH_LAST: &str = "区间信息:
@@ -48,28 +96,38 @@ const DEFAULT_FORMAT_WITH_LAST: &str = "区间信息:
         - 免费流量: [区间流量免费用量]
         - 收费流量: [区间流量收费用量]
         - 通用流量: [区间流量通用用量]
-        - 定向流量: [区间流量定向用量]
+        - 定向流量: [区间流量定向用量][#非零 通话总量]
     * 通话:
         - 总通话时长: [区间通话总用量]
         - 定向通话时长: [区间通话定向用量]
-        - 通用通话时长: [区间通话通用用量]
+        - 通用通话时长: [区间通话通用用量][/非零][#非零 短信总量]
+    * 短信:
+        - 总短信: [区间短信总用量]
+        - 定向短信: [区间短信定向用量]
+        - 通用短信: [区间短信通用用量][/非零]
 查询信息:
     时间: [时间]
     流量:
         * 总量:
-            - 总流量: [流量总量]
-            - 定向流量: [流量定向总量]
+            - 总流量: [流量总量][#非零 流量定向总量]
+            - 定向流量: [流量定向总量][/非零]
             - 通用流量: [流量通用总量]
         * 用量:
-            - 总流量: [流量总用量]
-            - 定向流量: [流量定向用量]
+            - 总流量: [流量总用量][#非零 流量定向总量]
+            - 定向流量: [流量定向用量][/非零]
             - 通用流量: [流量通用用量]
             - 免费流量: [流量免费用量]
             - 收费流量: [流量收费用量]
         * 余量:
-            - 总流量: [流量总余量]
-            - 定向流量: [流量定向余量]
-            - 通用流量: [流量通用余量]
+            - 总流量: [流量总余量][#非零 流量定向总量]
+            - 定向流量: [流量定向余量][/非零]
+            - 通用流量: [流量通用余量][#非零 结转流量总量]
+        * 结转:
+            - 剩余: [结转流量余量]
+            - 到期: [结转流量到期][/非零]
+        * 限速:
+            - 状态: [限速状态]
+            - 高速流量余量: [高速流量余量][#非零 通话总量]
     通话:
         * 总量:
             - 总通话时长: [通话总量]
@@ -82,26 +140,30 @@ const DEFAULT_FORMAT_WITH_LAST: &str = "区间信息:
         * 余量:
             - 总通话时长: [通话总余量]
             - 定向通话时长: [通话定向余量]
-            - 通用通话时长: [通话通用余量]";
+            - 通用通话时长: [通话通用余量][/非零][#非零 短信总量]
+    短信:
+        * 总量:
+            - 总短信: [短信总量]
+            - 定向短信: [短信定向总量]
+            - 通用短信: [短信通用总量]
+        * 用量:
+            - 总短信: [短信总用量]
+            - 定向短信: [短信定向用量]
+            - 通用短信: [短信通用用量]
+        * 余量:
+            - 总短信: [短信总余量]
+            - 定向短信: [短信定向余量]
+            - 通用短信: [短信通用余量][/非零][#非零 超出费用]
+    超出:
+        - 流量: [流量超出]
+        - 通话: [通话超出]
+        - 短信: [短信超出]
+        - 预估费用: [超出费用][/非零]";
 
 // 定义一个全局的日期时间格式化字符串
 const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
+// 按简体中文输出时长, 其他语言见 Locale::format_duration
 pub fn format_duration(duration: TimeDelta) -> String {
-    if duration.num_days() > 0 {
-        format!(
-            "{}天{}小时{}分钟",
-            duration.num_days(),
-            duration.num_hours() % 24,
-            duration.num_minutes() % 60
-        )
-    } else if duration.num_hours() > 0 {
-        format!(
-            "{}小时{}分钟",
-            duration.num_hours(),
-            duration.num_minutes() % 60
-        )
-    } else {
-        format!("{}分钟", duration.num_minutes())
-    }
+    Locale::ZhCn.format_duration(duration)
 }
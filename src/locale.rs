@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+// 模板输出使用的语言, 影响单位、无限标记、时长及状态文本, 流量单位(KB/MB/GB)不变
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "zh-TW")]
+    ZhTw,
+    #[serde(rename = "en")]
+    En,
+}
+
+// 需要按语言输出的词, 英文的单位带前导空格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Word {
+    Minute,
+    Hour,
+    Day,
+    Message,
+    Yuan,
+    Unlimited,
+    Missing,
+    Throttled,
+    NotThrottled,
+    Restored,
+    Limited,
+    General,
+}
+
+impl Locale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ZhCn => "zh-CN",
+            Self::ZhTw => "zh-TW",
+            Self::En => "en",
+        }
+    }
+
+    // 无限套餐的总量与余量
+    pub fn unlimited(self) -> &'static str {
+        self.word(Word::Unlimited)
+    }
+
+    // 天、小时、分钟, 不足一天时省略天, 不足一小时时只输出分钟
+    pub fn format_duration(self, duration: TimeDelta) -> String {
+        let (days, hours, minutes) = (
+            duration.num_days(),
+            duration.num_hours() % 24,
+            duration.num_minutes() % 60,
+        );
+        let (day, hour, minute) = match self {
+            Self::ZhCn => ("天", "小时", "分钟"),
+            Self::ZhTw => ("天", "小時", "分鐘"),
+            Self::En => ("d ", "h ", "m"),
+        };
+        if days > 0 {
+            format!("{days}{day}{hours}{hour}{minutes}{minute}")
+        } else if duration.num_hours() > 0 {
+            format!("{}{hour}{minutes}{minute}", duration.num_hours())
+        } else {
+            format!("{}{minute}", duration.num_minutes())
+        }
+    }
+
+    pub(crate) fn word(self, word: Word) -> &'static str {
+        match (self, word) {
+            (Self::ZhCn, Word::Minute) => "分钟",
+            (Self::ZhTw, Word::Minute) => "分鐘",
+            (Self::En, Word::Minute) => " min",
+            (Self::ZhCn, Word::Hour) => "小时",
+            (Self::ZhTw, Word::Hour) => "小時",
+            (Self::En, Word::Hour) => " h",
+            (Self::ZhCn | Self::ZhTw, Word::Day) => "天",
+            (Self::En, Word::Day) => " days",
+            (Self::ZhCn, Word::Message) => "条",
+            (Self::ZhTw, Word::Message) => "則",
+            (Self::En, Word::Message) => " SMS",
+            (Self::ZhCn | Self::ZhTw, Word::Yuan) => "元",
+            (Self::En, Word::Yuan) => " CNY",
+            (Self::ZhCn, Word::Unlimited) => "无限",
+            (Self::ZhTw, Word::Unlimited) => "無限",
+            (Self::En, Word::Unlimited) => "unlimited",
+            (Self::ZhCn, Word::Missing) => "无",
+            (Self::ZhTw, Word::Missing) => "無",
+            (Self::En, Word::Missing) => "N/A",
+            (Self::ZhCn | Self::ZhTw, Word::Throttled) => "已限速",
+            (Self::En, Word::Throttled) => "throttled",
+            (Self::ZhCn | Self::ZhTw, Word::NotThrottled) => "未限速",
+            (Self::En, Word::NotThrottled) => "not throttled",
+            (Self::ZhCn | Self::ZhTw, Word::Restored) => "已解除限速",
+            (Self::En, Word::Restored) => "restored",
+            (Self::ZhCn | Self::ZhTw, Word::Limited) => "定向",
+            (Self::En, Word::Limited) => "limited",
+            (Self::ZhCn | Self::ZhTw, Word::General) => "通用",
+            (Self::En, Word::General) => "general",
+        }
+    }
+}
+
+// 接受 zh-CN、zh-TW、en, 不区分大小写, 下划线与连字符等价
+impl FromStr for Locale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.replace('_', "-").to_ascii_lowercase().as_str() {
+            "zh-cn" | "zh" => Ok(Self::ZhCn),
+            "zh-tw" => Ok(Self::ZhTw),
+            "en" | "en-us" => Ok(Self::En),
+            _ => Err(Error::Format(format!("未知的语言 {s}"))),
+        }
+    }
+}
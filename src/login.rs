@@ -0,0 +1,234 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use rand_core::{OsRng, RngCore};
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
+use serde::Deserialize;
+
+use crate::client::{default_client, ChinaUnicomClient};
+use crate::error::{Error, Result};
+use crate::online::{extract_ecs_cookies, OnlineResp, APP_VERSION};
+use crate::query::read_body;
+
+const SEND_SMS_CODE_PATH: &str = "/mobileService/sendRadomNum.htm";
+const SMS_LOGIN_PATH: &str = "/mobileService/radomLogin.htm";
+const PASSWORD_LOGIN_PATH: &str = "/mobileService/login.htm";
+
+// 联通客户端内置的登录公钥(base64 编码的 DER)
+pub const DEFAULT_LOGIN_PUBLIC_KEY: &str = "MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDc+CZK9bBA9IU+gZUOc6FUGu7yO9WpTNB0PzmgFBh96Mg1WrovD1oqZ+eIF4LjvxKXGOdI79JRdve9NPhQo07+uqGQgE4imwNnRx7PFtCRryiIEcUoavuNtuRVoBAm6qdB0SrctgaqGfLgKvZHOnwTjyNqjBUxzMeQlEC2czEMSwIDAQAB";
+
+// 密码登录需要图形验证码时返回的 code
+const CAPTCHA_CODES: &[&str] = &["4"];
+
+// 密码登录需要短信二次验证时返回的 code
+const SECOND_FACTOR_CODES: &[&str] = &["8"];
+
+// code 无法识别时才按 desc 判断, 只匹配明确要求验证的说法
+// 不包含 "短信验证码", 否则短信验证码错误也会被当作需要二次验证
+const CAPTCHA_KEYWORDS: &[&str] = &["图形验证码", "图片验证码"];
+const SECOND_FACTOR_KEYWORDS: &[&str] = &["二次验证", "安全验证", "非常用设备"];
+
+// 密码登录的结果, 需要验证码时由调用方完成验证后重新登录
+#[derive(Debug, Clone)]
+pub enum PasswordLoginOutcome {
+    Success(OnlineResp),
+    // 需要输入图形验证码
+    CaptchaRequired { code: String, desc: String },
+    // 需要短信等二次验证, 可改用 sms_login
+    SecondFactorRequired { code: String, desc: String },
+}
+
+// 解析 PEM 或 base64 编码的 DER 公钥
+pub(crate) fn parse_public_key(key: &str) -> Result<RsaPublicKey> {
+    let key = key.trim();
+    let parsed = if key.starts_with("-----BEGIN") {
+        RsaPublicKey::from_public_key_pem(key).map_err(|e| e.to_string())
+    } else {
+        STANDARD
+            .decode(key)
+            .map_err(|e| e.to_string())
+            .and_then(|der| RsaPublicKey::from_public_key_der(&der).map_err(|e| e.to_string()))
+    };
+    parsed.map_err(|e| Error::Config(format!("登录公钥无效: {e}")))
+}
+
+// 与客户端一致: 明文后追加 6 位随机数, 使用 PKCS#1 v1.5 加密后 base64 编码
+fn encrypt_field(key: &RsaPublicKey, value: &str) -> Result<String> {
+    let salt = OsRng.next_u32() % 900_000 + 100_000;
+    let plaintext = format!("{value}{salt}");
+    let ciphertext = key
+        .encrypt(&mut OsRng, Pkcs1v15Encrypt, plaintext.as_bytes())
+        .map_err(|e| Error::Secret(format!("无法加密登录信息: {e}")))?;
+    Ok(STANDARD.encode(ciphertext))
+}
+
+// 登录相关接口成功时返回的 code
+const LOGIN_SUCCESS_CODE: &str = "0";
+
+// 登录相关接口的响应, 失败时只有 code/desc
+#[derive(Default, Debug, Deserialize)]
+pub(crate) struct LoginEnvelope {
+    #[serde(default)]
+    pub code: Option<serde_json::Value>,
+    #[serde(default)]
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub token_online: Option<String>,
+}
+
+impl LoginEnvelope {
+    pub(crate) fn parse(body: &str) -> Result<Self> {
+        serde_json::from_str(body).map_err(|e| Error::unexpected(e, body))
+    }
+
+    pub(crate) fn code(&self) -> String {
+        match &self.code {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(code)) => code.clone(),
+            Some(code) => code.to_string(),
+        }
+    }
+
+    pub(crate) fn is_success(&self) -> bool {
+        self.code() == LOGIN_SUCCESS_CODE
+    }
+
+    pub(crate) fn desc_contains(&self, keywords: &[&str]) -> bool {
+        let desc = self.desc.as_deref().unwrap_or_default();
+        keywords.iter().any(|keyword| desc.contains(keyword))
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        Error::LoginFailed {
+            code: self.code(),
+            desc: self.desc.unwrap_or_default(),
+        }
+    }
+}
+
+pub async fn send_sms_code(mobile: &str, app_id: &str) -> Result<()> {
+    default_client()?.send_sms_code(mobile, app_id).await
+}
+
+pub async fn sms_login(mobile: &str, code: &str, app_id: &str) -> Result<OnlineResp> {
+    default_client()?.sms_login(mobile, code, app_id).await
+}
+
+pub async fn password_login(
+    mobile: &str,
+    password: &str,
+    app_id: &str,
+) -> Result<PasswordLoginOutcome> {
+    default_client()?
+        .password_login(mobile, password, app_id)
+        .await
+}
+
+impl ChinaUnicomClient {
+    // 向手机号发送短信验证码
+    pub async fn send_sms_code(&self, mobile: &str, app_id: &str) -> Result<()> {
+        let resp = self
+            .http
+            .post(self.url(SEND_SMS_CODE_PATH))
+            .form(&[
+                ("mobile", mobile),
+                ("appId", app_id),
+                ("version", APP_VERSION),
+                ("loginCodeLen", "6"),
+            ])
+            .send()
+            .await?;
+
+        let body = read_body(resp).await?;
+        let envelope = LoginEnvelope::parse(&body)?;
+        if !envelope.is_success() {
+            return Err(envelope.into_error());
+        }
+        Ok(())
+    }
+
+    // 使用短信验证码登录, 返回 token_online 与 ecs cookie
+    pub async fn sms_login(&self, mobile: &str, code: &str, app_id: &str) -> Result<OnlineResp> {
+        let resp = self
+            .http
+            .post(self.url(SMS_LOGIN_PATH))
+            .form(&[
+                ("mobile", mobile),
+                ("password", code),
+                ("appId", app_id),
+                ("version", APP_VERSION),
+            ])
+            .send()
+            .await?;
+
+        let cookie = extract_ecs_cookies(&resp);
+        let body = read_body(resp).await?;
+        let envelope = LoginEnvelope::parse(&body)?;
+        if !envelope.is_success() {
+            return Err(envelope.into_error());
+        }
+
+        let cookie =
+            cookie.ok_or_else(|| Error::MissingCookie("ecs_token, ecs_acc".to_string()))?;
+        let online_token = envelope
+            .token_online
+            .ok_or_else(|| Error::unexpected("登录响应中缺少 token_online", body))?;
+
+        Ok(OnlineResp {
+            online_token,
+            cookie,
+        })
+    }
+
+    // 使用服务密码登录, 手机号与密码经公钥加密后提交
+    pub async fn password_login(
+        &self,
+        mobile: &str,
+        password: &str,
+        app_id: &str,
+    ) -> Result<PasswordLoginOutcome> {
+        let encrypted_mobile = encrypt_field(&self.login_public_key, mobile)?;
+        let encrypted_password = encrypt_field(&self.login_public_key, password)?;
+        let reqtime = Local::now().timestamp_millis().to_string();
+        let resp = self
+            .http
+            .post(self.url(PASSWORD_LOGIN_PATH))
+            .form(&[
+                ("mobile", encrypted_mobile.as_str()),
+                ("password", encrypted_password.as_str()),
+                ("appId", app_id),
+                ("version", APP_VERSION),
+                ("isRemberPwd", "true"),
+                ("reqtime", &reqtime),
+            ])
+            .send()
+            .await?;
+
+        let cookie = extract_ecs_cookies(&resp);
+        let body = read_body(resp).await?;
+        let envelope = LoginEnvelope::parse(&body)?;
+        if !envelope.is_success() {
+            let (code, desc) = (envelope.code(), envelope.desc.clone().unwrap_or_default());
+            let captcha = CAPTCHA_CODES.contains(&code.as_str());
+            let second_factor = SECOND_FACTOR_CODES.contains(&code.as_str());
+            let known = captcha || second_factor;
+            return if captcha || (!known && envelope.desc_contains(CAPTCHA_KEYWORDS)) {
+                Ok(PasswordLoginOutcome::CaptchaRequired { code, desc })
+            } else if second_factor || (!known && envelope.desc_contains(SECOND_FACTOR_KEYWORDS)) {
+                Ok(PasswordLoginOutcome::SecondFactorRequired { code, desc })
+            } else {
+                Err(envelope.into_error())
+            };
+        }
+
+        let cookie =
+            cookie.ok_or_else(|| Error::MissingCookie("ecs_token, ecs_acc".to_string()))?;
+        let online_token = envelope
+            .token_online
+            .ok_or_else(|| Error::unexpected("登录响应中缺少 token_online", body))?;
+
+        Ok(PasswordLoginOutcome::Success(OnlineResp {
+            online_token,
+            cookie,
+        }))
+    }
+}
@@ -1,71 +1,93 @@
-use crate::CLIENT;
-use anyhow::Result;
+use crate::client::{default_client, ChinaUnicomClient};
+use crate::error::{Error, Result};
+use crate::query::read_envelope;
+use crate::secret::REDACTED;
+use cookie_store::RawCookie;
 use reqwest::{
     header::{CONTENT_TYPE, SET_COOKIE},
     Response,
 };
 use serde::Deserialize;
+use std::fmt::{self, Debug};
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct OnlineResp {
     pub online_token: String,
     pub cookie: String,
 }
 
+impl Debug for OnlineResp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnlineResp")
+            .field("online_token", &REDACTED)
+            .field("cookie", &REDACTED)
+            .finish()
+    }
+}
+
 #[derive(Deserialize)]
 pub struct RawOnlineResp {
     pub token_online: String,
 }
 
+const ONLINE_PATH: &str = "/mobileService/onLine.htm";
+
+// 请求中携带的客户端版本
+pub(crate) const APP_VERSION: &str = "iphone_c@9.0100";
+
 pub async fn online(token_online: &str, app_id: &str) -> Result<OnlineResp> {
-    let url = "https://m.client.10010.com/mobileService/onLine.htm";
-    let body = format!("appId={app_id}&token_online={token_online}&version=iphone_c@9.0100");
-    let resp = CLIENT
-        .post(url)
-        .body(body)
-        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .send()
-        .await?;
+    default_client()?.online(token_online, app_id).await
+}
 
-    let cookie = extract_ecs_cookies(&resp).ok_or(anyhow::anyhow!("Failed to get new cookie"))?;
+impl ChinaUnicomClient {
+    pub async fn online(&self, token_online: &str, app_id: &str) -> Result<OnlineResp> {
+        let body = format!("appId={app_id}&token_online={token_online}&version={APP_VERSION}");
+        let resp = self
+            .http
+            .post(self.url(ONLINE_PATH))
+            .body(body)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .send()
+            .await?;
 
-    let raw_resp: RawOnlineResp = resp.json().await?;
+        let cookie = extract_ecs_cookies(&resp);
 
-    let resp = OnlineResp {
-        online_token: raw_resp.token_online,
-        cookie,
-    };
+        // token_online 失效时不会下发 cookie, 先检查 code/desc 给出更明确的错误
+        let body = read_envelope(resp).await?;
+        let cookie =
+            cookie.ok_or_else(|| Error::MissingCookie("ecs_token, ecs_acc".to_string()))?;
+        let raw_resp: RawOnlineResp =
+            serde_json::from_str(&body).map_err(|e| Error::unexpected(e, body))?;
 
-    Ok(resp)
+        let resp = OnlineResp {
+            online_token: raw_resp.token_online,
+            cookie,
+        };
+
+        Ok(resp)
+    }
 }
-fn extract_ecs_cookies(response: &Response) -> Option<String> {
-    // 初始化变量用于存储所需的 Cookie
+
+pub(crate) fn extract_ecs_cookies(response: &Response) -> Option<String> {
     let mut ecs_token: Option<String> = None;
     let mut ecs_acc: Option<String> = None;
 
-    // 遍历所有 Set-Cookie 头部字段
-    for cookie in response.headers().get_all(SET_COOKIE).iter() {
-        if let Ok(cookie_str) = cookie.to_str() {
-            // 分割每个 Set-Cookie 字段的值为键值对
-            for part in cookie_str.split("; ") {
-                if let Some((key, value)) = part.split_once('=') {
-                    // 检查是否为 ecs_token 或 ecs_acc
-                    match key {
-                        "ecs_token" => ecs_token = Some(value.to_string()),
-                        "ecs_acc" => ecs_acc = Some(value.to_string()),
-                        _ => {}
-                    }
-                }
-            }
-        }
-
-        // 如果找到了两个字段，立即返回
-        if ecs_token.is_some() && ecs_acc.is_some() {
-            break;
+    // 每个 Set-Cookie 头部只有开头的 name=value 是 cookie, 其余为 Path、Expires 等属性
+    for header in response.headers().get_all(SET_COOKIE).iter() {
+        let Some(cookie) = header
+            .to_str()
+            .ok()
+            .and_then(|header| RawCookie::parse(header).ok())
+        else {
+            continue;
+        };
+        match cookie.name() {
+            "ecs_token" => ecs_token = Some(cookie.value().to_string()),
+            "ecs_acc" => ecs_acc = Some(cookie.value().to_string()),
+            _ => {}
         }
     }
 
-    // 构建并返回目标 Cookie 字符串
     match (ecs_token, ecs_acc) {
         (Some(token), Some(acc)) => Some(format!("ecs_token={}; ecs_acc={}", token, acc)),
         _ => None,
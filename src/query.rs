@@ -1,279 +1,549 @@
-use anyhow::Result;
-use chrono::Local;
-use reqwest::header::{ACCEPT, ACCEPT_LANGUAGE, CONTENT_TYPE, COOKIE, REFERER, USER_AGENT};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use reqwest::header::{ACCEPT, ACCEPT_LANGUAGE, CONTENT_TYPE, COOKIE, REFERER};
+use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::data::ChinaUnicomData;
-use crate::CLIENT;
+use crate::amount::DataAmount;
+use crate::client::{default_client, ChinaUnicomClient};
+use crate::data::{ChinaUnicomData, PackageUsage};
+use crate::error::{Error, Result};
+use crate::lenient;
+use crate::quota::{Quantity, Quota};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct ChinaUnicomResponse {
-    // pub online_customer_service_switch: bool,
-    // pub new_tw_flag: String,
-    // pub charging_capacity_link: String,
-    // pub buy_voice_link: String,
-    // pub can_use_sms_all: String,
-    // #[serde(rename = "RzbResources")]
-    // pub rzb_resources: Vec<RzbResource>,
-    // pub use_daily_percent: String,
-    // #[serde(rename = "Topblock")]
-    // pub topblock: String,
-    // #[serde(rename = "XsbResources")]
-    // pub xsb_resources: Vec<XsbResource>,
-    // pub rzb_all_use: String,
-    // pub cs_img: String,
-    // pub languageflag: String,
-    // pub sum_percent: String,
-    // #[serde(rename = "MlResources")]
-    // pub ml_resources: Vec<MlResource>,
-    // pub canuse_voice_all_unit: String,
-    // pub crowdfunding_flag: bool,
-    // pub all_user_flow: String,
-    // pub sms_head_used: i64,
-    // #[serde(rename = "TwResources")]
-    // pub tw_resources: Vec<Value>,
-    // #[serde(rename = "accountBAR")]
-    // pub account_bar: Vec<AccountBar>,
-    // pub subscribe_to_text_messages_link: String,
-    // pub voice_exceed: i64,
-    // pub code: String,
-    // pub canuse_flow_all_unit: String,
+    #[serde(deserialize_with = "lenient::bool")]
+    pub online_customer_service_switch: bool,
+    #[serde(deserialize_with = "lenient::string")]
+    pub new_tw_flag: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub charging_capacity_link: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub buy_voice_link: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub can_use_sms_all: String,
+    #[serde(rename = "RzbResources", deserialize_with = "lenient::vec")]
+    pub rzb_resources: Vec<RzbResource>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub use_daily_percent: String,
+    #[serde(rename = "Topblock", deserialize_with = "lenient::string")]
+    pub topblock: String,
+    #[serde(rename = "XsbResources", deserialize_with = "lenient::vec")]
+    pub xsb_resources: Vec<XsbResource>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub rzb_all_use: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub cs_img: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub languageflag: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub sum_percent: String,
+    #[serde(rename = "MlResources", deserialize_with = "lenient::vec")]
+    pub ml_resources: Vec<MlResource>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub canuse_voice_all_unit: String,
+    #[serde(deserialize_with = "lenient::bool")]
+    pub crowdfunding_flag: bool,
+    #[serde(deserialize_with = "lenient::string")]
+    pub all_user_flow: String,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub sms_head_used: i64,
+    #[serde(rename = "TwResources", deserialize_with = "lenient::vec")]
+    pub tw_resources: Vec<Value>,
+    #[serde(rename = "accountBAR", deserialize_with = "lenient::vec")]
+    pub account_bar: Vec<AccountBar>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub subscribe_to_text_messages_link: String,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub voice_exceed: i64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub code: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub canuse_flow_all_unit: String,
+    #[serde(deserialize_with = "lenient::f64")]
     pub sumresource: f64,
-    // pub city_code: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub city_code: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub sum: String,
-    // pub used_voice_noun_explain: String,
-    // pub cs_entrance_url: String,
-    // pub flow_exceed: f64,
-    // pub balancesumqry: String,
-    // #[serde(rename = "wangTTcUrl")]
-    // pub wang_ttc_url: String,
-    // pub use_percent: Vec<UsePercent>,
-    // pub voice_sumresource: i64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub used_voice_noun_explain: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub cs_entrance_url: String,
+    // 超出套餐的流量, 接口未标明单位, 与 sumresource 等流量字段一样按 MB 处理
+    #[serde(deserialize_with = "lenient::f64")]
+    pub flow_exceed: f64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub balancesumqry: String,
+    #[serde(rename = "wangTTcUrl", deserialize_with = "lenient::string")]
+    pub wang_ttc_url: String,
+    #[serde(deserialize_with = "lenient::vec")]
+    pub use_percent: Vec<UsePercent>,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub voice_sumresource: i64,
+    #[serde(deserialize_with = "lenient::string")]
     pub package_name: String,
-    // pub can_use_value_all: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub can_use_value_all: String,
+    #[serde(deserialize_with = "lenient::object")]
     pub summary: Summary,
-    // #[serde(rename = "s1HistoryFlowDetails")]
-    // pub s1history_flow_details: Vec<Value>,
-    // pub reminder: String,
-    // pub province_code: String,
-    // pub package_id: String,
-    // pub usefree_percent: String,
-    // pub mobile: String,
+    #[serde(rename = "s1HistoryFlowDetails", deserialize_with = "lenient::vec")]
+    pub s1history_flow_details: Vec<Value>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub reminder: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub province_code: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub package_id: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub usefree_percent: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub mobile: String,
+    #[serde(deserialize_with = "lenient::vec")]
     pub resources: Vec<Resource>,
-    // pub traffic_prompts_are_exempted: String,
-    // pub left_month: i64,
-    // pub canuse_sms_all_unit: String,
-    // #[serde(rename = "s2HistoryFlowDetails")]
-    // pub s2history_flow_details: Vec<Value>,
-    // pub voice_head_used: i64,
-    // pub time: String,
-    // pub business_type: String,
-    // #[serde(rename = "MlProgrammeFlag")]
-    // pub ml_programme_flag: String,
-    // pub sms_sumresource: i64,
-    // pub balancedetailqry: String,
-    // pub sms_exceed: i64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub traffic_prompts_are_exempted: String,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub left_month: i64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub canuse_sms_all_unit: String,
+    #[serde(rename = "s2HistoryFlowDetails", deserialize_with = "lenient::vec")]
+    pub s2history_flow_details: Vec<Value>,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub voice_head_used: i64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub time: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub business_type: String,
+    #[serde(rename = "MlProgrammeFlag", deserialize_with = "lenient::string")]
+    pub ml_programme_flag: String,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub sms_sumresource: i64,
+    #[serde(deserialize_with = "lenient::string")]
+    pub balancedetailqry: String,
+    #[serde(deserialize_with = "lenient::i64")]
+    pub sms_exceed: i64,
 }
 
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct RzbResource {
-//     pub details: Vec<Value>,
-//     pub rzb_all_use: String,
-// }
-
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct XsbResource {
-//     pub details: Vec<Value>,
-// }
-
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct MlResource {
-//     pub details: Vec<Detail>,
-//     #[serde(rename = "type")]
-//     pub type_field: String,
-//     pub user_resource: String,
-// }
-
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct Detail {
-//     pub elem_type: String,
-//     pub end_date: String,
-//     pub end_xsb_date: String,
-//     pub fee_policy_id: String,
-//     pub fee_policy_name: String,
-//     pub hide_carry_forward_label: bool,
-//     pub limited: String,
-//     #[serde(rename = "use")]
-//     pub use_field: String,
-//     pub used_percent: String,
-// }
-
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct AccountBar {
-//     pub business_code: String,
-//     pub button_name: String,
-//     pub create_time: String,
-//     pub creator: String,
-//     pub creator_province_code: String,
-//     pub id: String,
-//     pub link_url: String,
-//     pub nettype: String,
-//     pub order_number: String,
-//     pub payment_type: String,
-//     pub province: String,
-//     pub status: String,
-//     pub user_id: String,
-// }
-
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct UsePercent {
-//     #[serde(rename = "Value")]
-//     pub value: String,
-//     #[serde(rename = "value")]
-//     pub value2: String,
-// }
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RzbResource {
+    #[serde(deserialize_with = "lenient::vec")]
+    pub details: Vec<Value>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub rzb_all_use: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct XsbResource {
+    #[serde(deserialize_with = "lenient::vec")]
+    pub details: Vec<Value>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MlResource {
+    #[serde(deserialize_with = "lenient::vec")]
+    pub details: Vec<Detail>,
+    #[serde(rename = "type", deserialize_with = "lenient::string")]
+    pub type_field: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub user_resource: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Detail {
+    #[serde(deserialize_with = "lenient::string")]
+    pub elem_type: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub end_date: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub end_xsb_date: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub fee_policy_id: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub fee_policy_name: String,
+    #[serde(deserialize_with = "lenient::bool")]
+    pub hide_carry_forward_label: bool,
+    #[serde(deserialize_with = "lenient::string")]
+    pub limited: String,
+    #[serde(rename = "use", deserialize_with = "lenient::string")]
+    pub use_field: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub used_percent: String,
+}
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
+pub struct AccountBar {
+    #[serde(deserialize_with = "lenient::string")]
+    pub business_code: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub button_name: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub create_time: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub creator: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub creator_province_code: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub id: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub link_url: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub nettype: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub order_number: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub payment_type: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub province: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub status: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub user_id: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct UsePercent {
+    #[serde(rename = "Value", deserialize_with = "lenient::string")]
+    pub value: String,
+    #[serde(rename = "value", deserialize_with = "lenient::string")]
+    pub value2: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Summary {
-    // pub domestic_daily_flow: String,
-    // pub fengdingstate: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub domestic_daily_flow: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub fengdingstate: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub free_flow: String,
-    // pub free_percent: String,
-    // pub percent: Vec<Percent>,
-    // pub remain_feng_ding: String,
-    // pub remain_percent: String,
-    // pub sum: String,
-    // pub use_percent: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub free_percent: String,
+    #[serde(deserialize_with = "lenient::vec")]
+    pub percent: Vec<Percent>,
+    // 封顶前剩余的高速流量, 接口未标明单位, 与 freeFlow 一样按 MB 处理
+    #[serde(deserialize_with = "lenient::string")]
+    pub remain_feng_ding: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub remain_percent: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub sum: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub use_percent: String,
 }
 
-// #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-// #[serde(rename_all = "camelCase")]
-// pub struct Percent {
-//     #[serde(rename = "Value")]
-//     pub value: String,
-//     #[serde(rename = "value")]
-//     pub value2: String,
-// }
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Percent {
+    #[serde(rename = "Value", deserialize_with = "lenient::string")]
+    pub value: String,
+    #[serde(rename = "value", deserialize_with = "lenient::string")]
+    pub value2: String,
+}
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct Resource {
+    #[serde(deserialize_with = "lenient::vec")]
     pub details: Vec<ResourceDetail>,
-    // pub remain_resource: String,
-    #[serde(rename = "type")]
+    #[serde(deserialize_with = "lenient::string")]
+    pub remain_resource: String,
+    #[serde(rename = "type", deserialize_with = "lenient::string")]
     pub type_field: String,
-    // pub url: String,
-    // pub user_resource: String,
-    // pub w_turl: String,
-    // pub wang_turl: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub url: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub user_resource: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub w_turl: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub wang_turl: String,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct ResourceDetail {
-    // pub add_up_item_name: String,
-    // pub addup_item_code: String,
-    // pub elem_type: String,
-    // pub end_date: String,
-    // pub end_xsb_date: String,
-    // pub fee_policy_id: String,
-    // pub fee_policy_name: String,
-    // pub hide_carry_forward_label: bool,
+    #[serde(deserialize_with = "lenient::string")]
+    pub add_up_item_name: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub addup_item_code: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub elem_type: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub end_date: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub end_xsb_date: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub start_date: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub fee_policy_id: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub fee_policy_name: String,
+    #[serde(deserialize_with = "lenient::bool")]
+    pub hide_carry_forward_label: bool,
+    #[serde(deserialize_with = "lenient::string")]
     pub limited: String,
-    // pub rb_flag: String,
-    // pub realresourcetype: String,
-    // pub remain: String,
-    // pub resource_source: String,
-    // pub resource_type: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub rb_flag: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub realresourcetype: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub remain: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub resource_source: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub resource_type: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub total: String,
-    // pub typemark: String,
-    #[serde(rename = "use")]
+    #[serde(deserialize_with = "lenient::string")]
+    pub typemark: String,
+    #[serde(rename = "use", deserialize_with = "lenient::string")]
     pub use_field: String,
-    // pub used_percent: String,
-    // pub xexceedvalue: String,
-    // pub before_remain: Option<String>,
-    // pub before_total: Option<String>,
-    // pub before_use: Option<String>,
-    // pub f_kused_percent: Option<String>,
-    // pub merge_flag: Option<String>,
-    // pub z_kused_percent: Option<String>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub used_percent: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub xexceedvalue: String,
+    #[serde(deserialize_with = "lenient::opt_string")]
+    pub before_remain: Option<String>,
+    #[serde(deserialize_with = "lenient::opt_string")]
+    pub before_total: Option<String>,
+    #[serde(deserialize_with = "lenient::opt_string")]
+    pub before_use: Option<String>,
+    #[serde(deserialize_with = "lenient::opt_string")]
+    pub f_kused_percent: Option<String>,
+    #[serde(deserialize_with = "lenient::opt_string")]
+    pub merge_flag: Option<String>,
+    #[serde(deserialize_with = "lenient::opt_string")]
+    pub z_kused_percent: Option<String>,
 }
 
 impl ChinaUnicomResponse {
-    pub fn to_unicom_data(&self) -> ChinaUnicomData {
-        let (sum_flow_used, free_flow_used, non_free_flow_used) =
-            self.get_flow_usage().unwrap_or((0.0, 0.0, 0.0));
-        let (sum_flow, limit_flow, non_limit_flow, limit_flow_used, non_limit_flow_used) =
-            self.get_flow_details().unwrap_or((0.0, 0.0, 0.0, 0.0, 0.0));
+    // 字段无法解析时返回 Error::Parse, 不再当作 0 用量
+    pub fn to_unicom_data(&self) -> Result<ChinaUnicomData> {
+        let (sum_flow_used, free_flow_used, non_free_flow_used) = self.get_flow_usage()?;
+        let (_, limit_flow, non_limit_flow, limit_flow_used, non_limit_flow_used) =
+            self.get_flow_details()?;
         let (
             sum_voice_used,
-            sum_voice,
+            _,
             limit_voice_used,
             limit_voice,
             non_limit_voice_used,
             non_limit_voice,
-        ) = self.get_voice_details().unwrap_or((0, 0, 0, 0, 0, 0));
+        ) = self.get_voice_details()?;
+        let (sum_sms_used, _, limit_sms_used, limit_sms, non_limit_sms_used, non_limit_sms) =
+            self.get_sms_details()?;
+
+        let (carry_over_flow, carry_over_flow_used, carry_over_flow_expiry) =
+            self.get_carry_over_flow()?;
 
-        ChinaUnicomData {
+        let (throttled, high_speed_flow_remaining) = self.get_throttle_state();
+
+        let limit_flow = Quota::from_usage(limit_flow, limit_flow_used);
+        let non_limit_flow = Quota::from_usage(non_limit_flow, non_limit_flow_used);
+        let limit_voice = Quota::from_usage(limit_voice, limit_voice_used);
+        let non_limit_voice = Quota::from_usage(non_limit_voice, non_limit_voice_used);
+        let limit_sms = Quota::from_usage(limit_sms, limit_sms_used);
+        let non_limit_sms = Quota::from_usage(non_limit_sms, non_limit_sms_used);
+
+        Ok(ChinaUnicomData {
             package_name: self.package_name.clone(),
             time: Local::now(),
             sum_flow_used,
             free_flow_used,
             non_free_flow_used,
-            sum_flow,
+            sum_flow: Quota::total(limit_flow, non_limit_flow),
             limit_flow,
             non_limit_flow,
             limit_flow_used,
             non_limit_flow_used,
             sum_voice_used,
-            sum_voice,
+            sum_voice: Quota::total(limit_voice, non_limit_voice),
             limit_voice_used,
             limit_voice,
             non_limit_voice_used,
             non_limit_voice,
+            sum_sms_used,
+            sum_sms: Quota::total(limit_sms, non_limit_sms),
+            limit_sms_used,
+            limit_sms,
+            non_limit_sms_used,
+            non_limit_sms,
+            throttled,
+            high_speed_flow_remaining,
+            flow_exceed: DataAmount::from_mb(self.flow_exceed),
+            voice_exceed: self.voice_exceed,
+            sms_exceed: self.sms_exceed,
+            carry_over_flow,
+            carry_over_flow_used,
+            carry_over_flow_expiry,
+            flow_packages: self.get_packages("flow", DataAmount::from_mb)?,
+            voice_packages: self.get_packages("voice", |amount| amount as i64)?,
+            sms_packages: self.get_packages("sms", |amount| amount as i64)?,
+        })
+    }
+}
+
+impl ResourceDetail {
+    // 是否为上月结转的资源
+    // 资费名称中的 "结转" 优先; 否则需有非 0 的 beforeTotal, 且接口未要求隐藏结转标签
+    pub fn is_carry_over(&self) -> bool {
+        if self.fee_policy_name.contains("结转") {
+            return true;
+        }
+        let has_before_total = self.before_total.as_deref().is_some_and(|before| {
+            let before = before.trim();
+            !before.is_empty() && before.parse::<f64>() != Ok(0.0)
+        });
+        has_before_total && !self.hide_carry_forward_label
+    }
+
+    // 结转资源的总量, total 为空时取 beforeTotal
+    pub fn carry_over_total(&self) -> Result<f64> {
+        let total = parse_amount("details.total", &self.total)?;
+        match &self.before_total {
+            Some(before) if total == 0.0 => parse_amount("details.beforeTotal", before),
+            _ => Ok(total),
         }
     }
+
+    // 结转资源的已用量, use 为空时取 beforeUse
+    pub fn carry_over_used(&self) -> Result<f64> {
+        let used = parse_amount("details.use", &self.use_field)?;
+        match &self.before_use {
+            Some(before) if used == 0.0 => parse_amount("details.beforeUse", before),
+            _ => Ok(used),
+        }
+    }
+
+    // 生效日期
+    pub fn start_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.start_date)
+    }
+
+    // 失效日期
+    pub fn end_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.end_date)
+    }
+}
+
+// 接口中的日期可能是 2024-09-30、20240930 或带有时间
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    let date = date.trim();
+    ["%Y-%m-%d", "%Y%m%d", "%Y/%m/%d"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(date, fmt).ok())
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S", "%Y%m%d%H%M%S"]
+                .iter()
+                .find_map(|fmt| NaiveDateTime::parse_from_str(date, fmt).ok())
+                .map(|time| time.date())
+        })
+}
+
+// 空字符串(字段缺失或为 null)视为 0, 其余无法解析的数量返回 Error::Parse
+fn parse_amount(field: &str, amount: &str) -> Result<f64> {
+    let amount = amount.trim();
+    if amount.is_empty() {
+        return Ok(0.0);
+    }
+    amount.parse().map_err(|e| Error::parse(field, e))
 }
 
 impl ChinaUnicomResponse {
-    fn parse_flow(flow: &str) -> Result<f64, String> {
-        flow.parse::<f64>()
-            .map(|f| f / 1024.0)
-            .map_err(|e| e.to_string())
+    // 接口中的流量单位为 MB
+    fn parse_flow(field: &str, flow: &str) -> Result<DataAmount> {
+        parse_amount(field, flow).map(DataAmount::from_mb)
+    }
+
+    fn parse_voice(field: &str, voice: &str) -> Result<i64> {
+        parse_amount(field, voice).map(|v| v as i64)
+    }
+
+    // 资源类型名称大小写不统一(如 flow、Voice), 比较时忽略大小写
+    pub fn find_resource(&self, type_field: &str) -> Option<&Resource> {
+        self.resources
+            .iter()
+            .find(|r| r.type_field.eq_ignore_ascii_case(type_field))
+    }
+
+    // 某类资源下每个资源包的用量, convert 将接口中的数量换算为对应单位
+    pub fn get_packages<T: Quantity>(
+        &self,
+        type_field: &str,
+        convert: impl Fn(f64) -> T,
+    ) -> Result<Vec<PackageUsage<T>>> {
+        let Some(resource) = self.find_resource(type_field) else {
+            return Ok(Vec::new());
+        };
+
+        resource
+            .details
+            .iter()
+            .map(|detail| {
+                let total = parse_amount(&format!("{type_field}.details.total"), &detail.total)?;
+                let used = parse_amount(&format!("{type_field}.details.use"), &detail.use_field)?;
+                // remain 为空时由总量与已用量推算
+                let remaining = match detail.remain.trim() {
+                    "" => total - used,
+                    remain => remain
+                        .parse()
+                        .map_err(|e| Error::parse(format!("{type_field}.details.remain"), e))?,
+                };
+                let used = convert(used);
+                // 与汇总字段一样, 总量为 0 却有用量时视为无限
+                let total = Quota::from_usage(convert(total), used);
+                Ok(PackageUsage {
+                    fee_policy_name: detail.fee_policy_name.clone(),
+                    limited: detail.limited == "1",
+                    total,
+                    used,
+                    remaining: total.map(|_| convert(remaining)),
+                    start_date: detail.start_date(),
+                    end_date: detail.end_date(),
+                    carry_over: detail.is_carry_over(),
+                })
+            })
+            .collect()
     }
 
-    pub fn get_flow_usage(&self) -> Result<(f64, f64, f64), String> {
-        let sum_flow = self.sumresource / 1024.0;
-        let free_flow = Self::parse_flow(&self.summary.free_flow)?;
-        let non_free_flow = sum_flow - free_flow;
+    // (总用量, 免费用量, 收费用量)
+    // sumresource 包含结转流量的用量, 总用量改由定向与通用用量相加, 与总量一样不含结转流量
+    pub fn get_flow_usage(&self) -> Result<(DataAmount, DataAmount, DataAmount)> {
+        let (_, _, _, limit_flow_used, non_limit_flow_used) = self.get_flow_details()?;
+        let sum_flow = limit_flow_used + non_limit_flow_used;
+        let free_flow = Self::parse_flow("summary.freeFlow", &self.summary.free_flow)?;
+        let non_free_flow = (sum_flow - free_flow).max(DataAmount::ZERO);
 
         Ok((sum_flow, free_flow, non_free_flow))
     }
 
-    pub fn get_flow_details(&self) -> Result<(f64, f64, f64, f64, f64), String> {
-        let mut sum_flow = 0.0;
-        let mut limit_flow = 0.0;
-        let mut non_limit_flow = 0.0;
-        let mut limit_flow_used = 0.0;
-        let mut non_limit_flow_used = 0.0;
+    pub fn get_flow_details(
+        &self,
+    ) -> Result<(DataAmount, DataAmount, DataAmount, DataAmount, DataAmount)> {
+        let mut sum_flow = DataAmount::ZERO;
+        let mut limit_flow = DataAmount::ZERO;
+        let mut non_limit_flow = DataAmount::ZERO;
+        let mut limit_flow_used = DataAmount::ZERO;
+        let mut non_limit_flow_used = DataAmount::ZERO;
 
         let details: &Resource = self
-            .resources
-            .iter()
-            .find(|r| r.type_field == "flow")
-            .ok_or("无流量包信息")?;
+            .find_resource("flow")
+            .ok_or_else(|| Error::parse("resources", "无流量包信息"))?;
 
-        for detail in &details.details {
-            let total = detail.total.parse::<f64>().map_err(|e| e.to_string())? / 1024.0;
-            let used = detail.use_field.parse::<f64>().map_err(|e| e.to_string())? / 1024.0;
+        // 结转流量单独统计, 不计入当月总量
+        for detail in details.details.iter().filter(|d| !d.is_carry_over()) {
+            let total = Self::parse_flow("flow.details.total", &detail.total)?;
+            let used = Self::parse_flow("flow.details.use", &detail.use_field)?;
             sum_flow += total;
 
             if detail.limited == "1" {
@@ -294,7 +564,44 @@ impl ChinaUnicomResponse {
         ))
     }
 
-    pub fn get_voice_details(&self) -> Result<(i64, i64, i64, i64, i64, i64), String> {
+    // (是否已封顶限速, 剩余高速流量), 未返回剩余高速流量时为 None
+    pub fn get_throttle_state(&self) -> (bool, Option<DataAmount>) {
+        let throttled = matches!(
+            self.summary.fengdingstate.trim(),
+            "1" | "true" | "TRUE" | "Y" | "y"
+        );
+        let remaining = self
+            .summary
+            .remain_feng_ding
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(DataAmount::from_mb);
+        (throttled, remaining)
+    }
+
+    // 上月结转流量的(总量, 已用, 最早失效日期)
+    pub fn get_carry_over_flow(&self) -> Result<(DataAmount, DataAmount, Option<NaiveDate>)> {
+        let Some(resource) = self.find_resource("flow") else {
+            return Ok((DataAmount::ZERO, DataAmount::ZERO, None));
+        };
+
+        let carry_over = resource.details.iter().filter(|d| d.is_carry_over());
+        let mut total = DataAmount::ZERO;
+        let mut used = DataAmount::ZERO;
+        let mut expiry = None;
+        for detail in carry_over {
+            total += DataAmount::from_mb(detail.carry_over_total()?);
+            used += DataAmount::from_mb(detail.carry_over_used()?);
+            expiry = match (expiry, detail.end_date()) {
+                (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                (a, b) => a.or(b),
+            };
+        }
+        Ok((total, used, expiry))
+    }
+
+    pub fn get_voice_details(&self) -> Result<(i64, i64, i64, i64, i64, i64)> {
         let mut sum_voice_used = 0;
         let mut sum_voice = 0;
         let mut limit_voice_used = 0;
@@ -303,14 +610,12 @@ impl ChinaUnicomResponse {
         let mut non_limit_voice = 0;
 
         let details: &Resource = self
-            .resources
-            .iter()
-            .find(|r| r.type_field == "Voice")
-            .ok_or("无通话包信息")?;
+            .find_resource("voice")
+            .ok_or_else(|| Error::parse("resources", "无通话包信息"))?;
 
         for detail in &details.details {
-            let used = detail.use_field.parse::<i64>().map_err(|e| e.to_string())?;
-            let total = detail.total.parse::<i64>().map_err(|e| e.to_string())?;
+            let used = Self::parse_voice("Voice.details.use", &detail.use_field)?;
+            let total = Self::parse_voice("Voice.details.total", &detail.total)?;
             sum_voice_used += used;
             sum_voice += total;
 
@@ -332,21 +637,193 @@ impl ChinaUnicomResponse {
             non_limit_voice,
         ))
     }
+
+    // (总用量, 总量, 定向用量, 定向总量, 通用用量, 通用总量)
+    pub fn get_sms_details(&self) -> Result<(i64, i64, i64, i64, i64, i64)> {
+        let mut sum_sms_used = 0;
+        let mut sum_sms = 0;
+        let mut limit_sms_used = 0;
+        let mut non_limit_sms_used = 0;
+        let mut limit_sms = 0;
+        let mut non_limit_sms = 0;
+
+        let Some(details) = self.find_resource("sms") else {
+            // 没有短信包明细时退回到汇总字段, 全部视为通用短信
+            let remaining = Self::parse_voice("canUseSmsAll", &self.can_use_sms_all)?;
+            let used = self.sms_sumresource;
+            return Ok((used, used + remaining, 0, 0, used, used + remaining));
+        };
+
+        for detail in &details.details {
+            let used = Self::parse_voice("sms.details.use", &detail.use_field)?;
+            let total = Self::parse_voice("sms.details.total", &detail.total)?;
+            sum_sms_used += used;
+            sum_sms += total;
+
+            if detail.limited == "1" {
+                limit_sms_used += used;
+                limit_sms += total;
+            } else {
+                non_limit_sms_used += used;
+                non_limit_sms += total;
+            }
+        }
+
+        Ok((
+            sum_sms_used,
+            sum_sms,
+            limit_sms_used,
+            limit_sms,
+            non_limit_sms_used,
+            non_limit_sms,
+        ))
+    }
 }
 
+const QUERY_PATH: &str =
+    "/servicequerybusiness/operationservice/queryOcsPackageFlowLeftContentRevisedInJune";
+
 pub async fn query_china_unicom_data(cookie: &str) -> Result<ChinaUnicomData> {
-    let url = "https://m.client.10010.com/servicequerybusiness/operationservice/queryOcsPackageFlowLeftContentRevisedInJune";
-
-    let response:ChinaUnicomResponse = CLIENT.post(url)
-        .header(ACCEPT, "application/json, text/plain, */*")
-        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .header(COOKIE, cookie)
-        .header(USER_AGENT, "Mozilla/5.0 (iPhone; CPU iPhone OS 16_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 unicom{version:iphone_c@11.0700}")
-        .header(REFERER, "https://img.client.10010.com/")
-        .header(ACCEPT_LANGUAGE, "zh-SG,zh-CN;q=0.9,zh-Hans;q=0.8")
-        .body("duanlianjieabc=&channelCode=&serviceType=&saleChannel=&externalSources=&contactCode=&ticket=&ticketPhone=&ticketChannel=&language=chinese")
-        .send()
-        .await?.json().await?;
-
-    Ok(response.to_unicom_data())
+    default_client()?.query_china_unicom_data(cookie).await
+}
+
+impl ChinaUnicomClient {
+    pub async fn query_china_unicom_data(&self, cookie: &str) -> Result<ChinaUnicomData> {
+        self.send_query(Some(cookie)).await
+    }
+
+    // 使用 cookie jar 中的 cookie 查询, 未配置 cookie jar 时返回 MissingCookie
+    pub async fn query(&self) -> Result<ChinaUnicomData> {
+        if self.cookie_jar.is_none() {
+            return Err(Error::MissingCookie("未配置 cookie jar".to_string()));
+        }
+        self.send_query(None).await
+    }
+
+    async fn send_query(&self, cookie: Option<&str>) -> Result<ChinaUnicomData> {
+        let mut request = self.http.post(self.url(QUERY_PATH));
+        if let Some(cookie) = cookie {
+            request = request.header(COOKIE, cookie);
+        }
+        let response = request
+            .header(ACCEPT, "application/json, text/plain, */*")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(REFERER, "https://img.client.10010.com/")
+            .header(ACCEPT_LANGUAGE, "zh-SG,zh-CN;q=0.9,zh-Hans;q=0.8")
+            .body("duanlianjieabc=&channelCode=&serviceType=&saleChannel=&externalSources=&contactCode=&ticket=&ticketPhone=&ticketChannel=&language=chinese")
+            .send()
+            .await?;
+
+        let body = read_envelope(response).await?;
+        let response: ChinaUnicomResponse =
+            serde_json::from_str(&body).map_err(|e| Error::unexpected(e, body))?;
+
+        response.to_unicom_data()
+    }
+}
+
+// 接口成功时返回的 code
+const SUCCESS_CODES: &[&str] = &["0000", "0"];
+
+// desc 中出现这些说法时认为是登录状态失效, 只匹配完整的说法, 避免误判其他错误
+const SESSION_EXPIRED_PHRASES: &[&str] = &[
+    "请重新登录",
+    "请重新登陆",
+    "请先登录",
+    "未登录",
+    "登录已失效",
+    "登录已过期",
+    "登录状态已失效",
+    "登录超时",
+    "token已失效",
+    "token失效",
+    "token已过期",
+];
+
+// html 页面中出现这些标记时认为是被重定向到了登录页
+const LOGIN_PAGE_MARKERS: &[&str] = &["<title>登录", "login.htm", "请登录", "请输入手机号"];
+
+// 联通接口共有的 code/desc 外壳
+#[derive(Default, Debug, Deserialize)]
+struct ResponseEnvelope {
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+    #[serde(default)]
+    desc: Option<String>,
+}
+
+// 检查 HTTP 状态码后读取响应体: 401 视为登录失效, 其余非 2xx(如 5xx)返回 Error::Http
+pub(crate) async fn read_body(response: Response) -> Result<String> {
+    let status = response.status();
+    let body = response.text().await?;
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(Error::SessionExpired {
+            code: status.as_u16().to_string(),
+            desc: "接口返回 401".to_string(),
+        });
+    }
+    if !status.is_success() {
+        return Err(Error::Http {
+            status: status.as_u16(),
+            body: body.into(),
+        });
+    }
+    Ok(body)
+}
+
+// 读取需要登录的接口的响应, 并检查是否被重定向到登录页以及 code/desc
+pub(crate) async fn read_envelope(response: Response) -> Result<String> {
+    let redirected_to_login = response.url().path().to_lowercase().contains("login");
+    let body = read_body(response).await?;
+    if redirected_to_login {
+        return Err(Error::SessionExpired {
+            code: "redirect".to_string(),
+            desc: "被重定向到登录页面".to_string(),
+        });
+    }
+    check_envelope(&body)?;
+    Ok(body)
+}
+
+// 在解析完整响应前先检查 code/desc, 区分登录失效与其他错误
+pub(crate) fn check_envelope(body: &str) -> Result<()> {
+    // cookie 失效时可能返回 html 登录页, 其他 html 页面(如网关错误页)不视为登录失效
+    if body.trim_start().starts_with('<') {
+        return if LOGIN_PAGE_MARKERS
+            .iter()
+            .any(|marker| body.contains(marker))
+        {
+            Err(Error::SessionExpired {
+                code: "html".to_string(),
+                desc: "返回了登录页面".to_string(),
+            })
+        } else {
+            Err(Error::unexpected("返回了 html 页面", body))
+        };
+    }
+
+    let envelope: ResponseEnvelope =
+        serde_json::from_str(body).map_err(|e| Error::unexpected(e, body))?;
+    let code = match envelope.code {
+        None | Some(serde_json::Value::Null) => return Ok(()),
+        Some(serde_json::Value::String(code)) => code,
+        Some(code) => code.to_string(),
+    };
+    if SUCCESS_CODES.contains(&code.as_str()) {
+        return Ok(());
+    }
+
+    let desc = envelope.desc.unwrap_or_default();
+    let lowercase = desc.to_lowercase();
+    if SESSION_EXPIRED_PHRASES
+        .iter()
+        .any(|phrase| lowercase.contains(phrase))
+    {
+        Err(Error::SessionExpired { code, desc })
+    } else {
+        Err(Error::unexpected(
+            format!("接口返回错误({code}): {desc}"),
+            body,
+        ))
+    }
 }
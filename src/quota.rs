@@ -0,0 +1,197 @@
+use std::{
+    fmt::{self, Display},
+    ops::{Add, Sub},
+};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::amount::DataAmount;
+
+// 可以计算余量与百分比的数量(流量、通话分钟数、短信条数)
+pub trait Quantity: Copy + PartialOrd + Default + Add<Output = Self> + Sub<Output = Self> {
+    fn as_f64(self) -> f64;
+}
+
+impl Quantity for i64 {
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Quantity for DataAmount {
+    fn as_f64(self) -> f64 {
+        self.as_kb() as f64
+    }
+}
+
+// 资源的总量, 无限套餐没有具体数值
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", remote = "Self")]
+pub enum Quota<T> {
+    Limited(T),
+    Unlimited,
+}
+
+// 序列化为 {"limited": 总量} 或 "unlimited"
+impl<T: Serialize> Serialize for Quota<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::serialize(self, serializer)
+    }
+}
+
+// 旧版本的 ChinaUnicomData 直接保存总量数值, 读取时视为有限总量
+// (旧记录中无限套餐的总量为 0, 无法还原为 Unlimited, 百分比仍按总量为 0 处理)
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Quota<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr<T> {
+            #[serde(with = "Quota")]
+            Tagged(Quota<T>),
+            Legacy(T),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Tagged(quota) => quota,
+            Repr::Legacy(total) => Self::Limited(total),
+        })
+    }
+}
+
+impl<T: Default> Default for Quota<T> {
+    fn default() -> Self {
+        Self::Limited(T::default())
+    }
+}
+
+impl<T: Copy> Quota<T> {
+    pub fn is_unlimited(&self) -> bool {
+        matches!(self, Self::Unlimited)
+    }
+
+    // 有限时的总量
+    pub fn limit(&self) -> Option<T> {
+        match self {
+            Self::Limited(total) => Some(*total),
+            Self::Unlimited => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Quota<U> {
+        match self {
+            Self::Limited(total) => Quota::Limited(f(total)),
+            Self::Unlimited => Quota::Unlimited,
+        }
+    }
+}
+
+impl<T: Quantity> Quota<T> {
+    // 接口不会直接标明无限, 总量为 0 却有用量时视为无限
+    pub(crate) fn from_usage(total: T, used: T) -> Self {
+        let zero = T::default();
+        if total == zero && used > zero {
+            Self::Unlimited
+        } else {
+            Self::Limited(total)
+        }
+    }
+
+    // 定向与通用合计的总量
+    // 通用无限时合计为无限; 定向只能用于特定应用, 定向无限时合计只计通用部分
+    pub fn total(limit: Self, non_limit: Self) -> Self {
+        match (limit, non_limit) {
+            (_, Self::Unlimited) => Self::Unlimited,
+            (Self::Unlimited, non_limit) => non_limit,
+            (Self::Limited(limit), Self::Limited(non_limit)) => Self::Limited(limit + non_limit),
+        }
+    }
+
+    // 已用 used 后的余量, 超出总量时为负数
+    pub fn remaining(&self, used: T) -> Self {
+        match self {
+            Self::Limited(total) => Self::Limited(*total - used),
+            Self::Unlimited => Self::Unlimited,
+        }
+    }
+
+    // 已用百分比(0~100, 超出时大于 100), 无限或总量为 0 时返回 None
+    pub fn percentage(&self, used: T) -> Option<f64> {
+        let total = self.limit()?.as_f64();
+        (total > 0.0).then(|| used.as_f64() / total * 100.0)
+    }
+}
+
+// 无限时显示为 "无限"
+impl<T: Display> Display for Quota<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Limited(total) => total.fmt(f),
+            Self::Unlimited => f.write_str("无限"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_tagged() {
+        assert_eq!(
+            serde_json::to_string(&Quota::Limited(100_i64)).unwrap(),
+            r#"{"limited":100}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Quota::<i64>::Unlimited).unwrap(),
+            r#""unlimited""#
+        );
+    }
+
+    #[test]
+    fn tagged_round_trip() {
+        for quota in [
+            Quota::Limited(DataAmount::from_gb(10.0)),
+            Quota::Limited(DataAmount::ZERO),
+            Quota::Unlimited,
+        ] {
+            let json = serde_json::to_string(&quota).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Quota<DataAmount>>(&json).unwrap(),
+                quota
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_number_is_limited() {
+        let quota: Quota<i64> = serde_json::from_str("100").unwrap();
+        assert_eq!(quota, Quota::Limited(100));
+
+        // 旧版本的流量总量是以 GB 为单位的小数
+        let quota: Quota<DataAmount> = serde_json::from_str("10.5").unwrap();
+        assert_eq!(quota, Quota::Limited(DataAmount::from_gb(10.5)));
+    }
+
+    #[test]
+    fn legacy_number_round_trips_as_tagged() {
+        let quota: Quota<i64> = serde_json::from_str("0").unwrap();
+        let json = serde_json::to_string(&quota).unwrap();
+
+        assert_eq!(json, r#"{"limited":0}"#);
+        assert_eq!(serde_json::from_str::<Quota<i64>>(&json).unwrap(), quota);
+    }
+
+    #[test]
+    fn unknown_value_is_rejected() {
+        assert!(serde_json::from_str::<Quota<i64>>(r#""infinite""#).is_err());
+        assert!(serde_json::from_str::<Quota<i64>>(r#"{"limited":"100"}"#).is_err());
+        assert!(serde_json::from_str::<Quota<i64>>("null").is_err());
+    }
+
+    #[test]
+    fn zero_total_with_usage_is_unlimited() {
+        assert_eq!(Quota::from_usage(0_i64, 5), Quota::Unlimited);
+        assert_eq!(Quota::from_usage(0_i64, 0), Quota::Limited(0));
+        assert_eq!(Quota::from_usage(100_i64, 5), Quota::Limited(100));
+    }
+}
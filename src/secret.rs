@@ -0,0 +1,277 @@
+use std::{
+    fmt::{self, Debug},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+
+use crate::error::{Error, Result};
+use crate::store::{write_atomic, CredentialStore, Credentials};
+
+// Debug 输出中代替 cookie、token 等敏感值
+pub(crate) const REDACTED: &str = "<redacted>";
+
+// 加密文件格式: base64(MAGIC | salt | nonce | 密文)
+// 密钥由 argon2id 从口令或密钥文件和随机盐派生, 使用 XChaCha20-Poly1305 加密并认证
+const MAGIC: &[u8; 4] = b"CUS1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+
+// 用于派生加密密钥的口令或密钥文件内容
+#[derive(Clone)]
+pub enum SecretKey {
+    Passphrase(String),
+    KeyFile(Vec<u8>),
+}
+
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Passphrase(_) => f.debug_tuple("Passphrase").field(&REDACTED).finish(),
+            Self::KeyFile(_) => f.debug_tuple("KeyFile").field(&REDACTED).finish(),
+        }
+    }
+}
+
+impl SecretKey {
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        Self::Passphrase(passphrase.into())
+    }
+
+    pub fn from_key_file(path: impl AsRef<Path>) -> Result<Self> {
+        let key = fs::read(path)?;
+        if key.is_empty() {
+            return Err(Error::Secret("密钥文件为空".to_string()));
+        }
+        Ok(Self::KeyFile(key))
+    }
+
+    fn material(&self) -> &[u8] {
+        match self {
+            Self::Passphrase(passphrase) => passphrase.as_bytes(),
+            Self::KeyFile(key) => key,
+        }
+    }
+
+    fn derive(&self, salt: &[u8]) -> Result<Key> {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(self.material(), salt, &mut key)
+            .map_err(|e| Error::Secret(format!("无法派生密钥: {e}")))?;
+        Ok(key)
+    }
+}
+
+// 加密任意内容, 返回可直接写入文本文件的 base64 字符串
+pub fn encrypt(plaintext: &[u8], key: &SecretKey) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let cipher = XChaCha20Poly1305::new(&key.derive(&salt)?);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: MAGIC,
+            },
+        )
+        .map_err(|_| Error::Secret("加密失败".to_string()))?;
+
+    let mut data = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(STANDARD.encode(data))
+}
+
+// 解密 encrypt 的输出, 口令错误或内容被篡改时返回 Error::Secret
+pub fn decrypt(data: &str, key: &SecretKey) -> Result<Vec<u8>> {
+    let data = STANDARD
+        .decode(data.trim())
+        .map_err(|e| Error::Secret(format!("不是有效的加密文件: {e}")))?;
+    if data.len() < HEADER_LEN || !data.starts_with(MAGIC) {
+        return Err(Error::Secret("不是有效的加密文件".to_string()));
+    }
+
+    let (salt, rest) = data[MAGIC.len()..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(&key.derive(salt)?);
+    cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: MAGIC,
+            },
+        )
+        .map_err(|_| Error::Secret("口令错误或文件已损坏".to_string()))
+}
+
+// 加密保存凭据的存储, 文件内容为 encrypt 后的 toml
+#[derive(Debug, Clone)]
+pub struct EncryptedCredentialStore {
+    path: PathBuf,
+    key: SecretKey,
+}
+
+impl EncryptedCredentialStore {
+    pub fn new(path: impl Into<PathBuf>, key: SecretKey) -> Self {
+        Self {
+            path: path.into(),
+            key,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl CredentialStore for EncryptedCredentialStore {
+    fn load(&self) -> Result<Option<Credentials>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let plaintext = decrypt(&content, &self.key)?;
+        let plaintext = String::from_utf8(plaintext)
+            .map_err(|e| Error::Store(format!("凭据不是有效的 utf-8: {e}")))?;
+        let credentials = toml::from_str(&plaintext)
+            .map_err(|e| Error::Store(format!("无法解析凭据文件 {}: {e}", self.path.display())))?;
+        Ok(Some(credentials))
+    }
+
+    fn save(&self, credentials: &Credentials) -> Result<()> {
+        let content = toml::to_string(credentials)
+            .map_err(|e| Error::Store(format!("无法序列化凭据: {e}")))?;
+        let content = encrypt(content.as_bytes(), &self.key)?;
+        write_atomic(&self.path, content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::temp_dir;
+
+    fn credentials() -> Credentials {
+        Credentials {
+            token_online: "secret-token".to_string(),
+            app_id: "app".to_string(),
+            cookie: Some("ecs_token=t; ecs_acc=a".to_string()),
+        }
+    }
+
+    fn assert_secret_error(result: Result<Vec<u8>>) {
+        assert!(matches!(result, Err(Error::Secret(_))), "{result:?}");
+    }
+
+    #[test]
+    fn passphrase_round_trip() {
+        let path = temp_dir("secret-passphrase").join("credentials.enc");
+        let store = EncryptedCredentialStore::new(&path, SecretKey::passphrase("correct horse"));
+
+        store.save(&credentials()).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("secret-token"));
+        assert!(!content.contains("ecs_token"));
+        assert_eq!(store.load().unwrap(), Some(credentials()));
+    }
+
+    #[test]
+    fn key_file_round_trip() {
+        let dir = temp_dir("secret-key-file");
+        let key_path = dir.join("key");
+        fs::write(&key_path, [7u8; 32]).unwrap();
+        let key = SecretKey::from_key_file(&key_path).unwrap();
+        let store = EncryptedCredentialStore::new(dir.join("credentials.enc"), key);
+
+        store.save(&credentials()).unwrap();
+
+        assert_eq!(store.load().unwrap(), Some(credentials()));
+    }
+
+    #[test]
+    fn empty_key_file_is_rejected() {
+        let key_path = temp_dir("secret-empty-key").join("key");
+        fs::write(&key_path, b"").unwrap();
+
+        let err = SecretKey::from_key_file(&key_path).unwrap_err();
+        assert!(matches!(err, Error::Secret(_)), "{err:?}");
+    }
+
+    #[test]
+    fn wrong_key_is_secret_error() {
+        let path = temp_dir("secret-wrong-key").join("credentials.enc");
+        EncryptedCredentialStore::new(&path, SecretKey::passphrase("right"))
+            .save(&credentials())
+            .unwrap();
+
+        let err = EncryptedCredentialStore::new(&path, SecretKey::passphrase("wrong"))
+            .load()
+            .unwrap_err();
+        assert!(matches!(err, Error::Secret(_)), "{err:?}");
+
+        let err = EncryptedCredentialStore::new(&path, SecretKey::KeyFile(b"wrong".to_vec()))
+            .load()
+            .unwrap_err();
+        assert!(matches!(err, Error::Secret(_)), "{err:?}");
+    }
+
+    #[test]
+    fn truncated_ciphertext_is_secret_error() {
+        let key = SecretKey::passphrase("key");
+        let data = STANDARD
+            .decode(encrypt(b"plaintext", &key).unwrap())
+            .unwrap();
+
+        for len in [0, MAGIC.len(), HEADER_LEN - 1, HEADER_LEN, data.len() - 1] {
+            assert_secret_error(decrypt(&STANDARD.encode(&data[..len]), &key));
+        }
+        assert_secret_error(decrypt("不是 base64", &key));
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_secret_error() {
+        let key = SecretKey::passphrase("key");
+        let data = STANDARD
+            .decode(encrypt(b"plaintext", &key).unwrap())
+            .unwrap();
+
+        // 分别篡改盐、nonce 与密文
+        for index in [MAGIC.len(), MAGIC.len() + SALT_LEN, data.len() - 1] {
+            let mut tampered = data.clone();
+            tampered[index] ^= 1;
+            assert_secret_error(decrypt(&STANDARD.encode(&tampered), &key));
+        }
+    }
+
+    #[test]
+    fn bad_magic_is_secret_error() {
+        let key = SecretKey::passphrase("key");
+        let mut data = STANDARD
+            .decode(encrypt(b"plaintext", &key).unwrap())
+            .unwrap();
+        data[..MAGIC.len()].copy_from_slice(b"CUS0");
+
+        assert_secret_error(decrypt(&STANDARD.encode(&data), &key));
+    }
+
+    #[test]
+    fn debug_redacts_key() {
+        let output = format!("{:?}", SecretKey::passphrase("hunter2"));
+        assert!(!output.contains("hunter2"), "{output}");
+    }
+}
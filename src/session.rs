@@ -0,0 +1,163 @@
+use std::{
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use crate::client::ChinaUnicomClient;
+use crate::data::ChinaUnicomData;
+use crate::error::{Error, Result};
+use crate::online::OnlineResp;
+use crate::secret::REDACTED;
+use crate::store::{CredentialStore, Credentials};
+
+// 登录会话: 保存 token_online、app_id 与当前 cookie,
+// cookie 失效时通过 online() 自动刷新并重试一次。
+// 客户端配置了 cookie jar 时, cookie 由 cookie jar 管理并自动携带
+#[derive(Clone)]
+pub struct Session {
+    client: ChinaUnicomClient,
+    token_online: String,
+    app_id: String,
+    cookie: Option<String>,
+    // 刷新成功后写入轮换后的凭据
+    store: Option<Arc<dyn CredentialStore>>,
+}
+
+impl Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("client", &self.client)
+            .field("token_online", &REDACTED)
+            .field("app_id", &self.app_id)
+            .field("cookie", &self.cookie.as_ref().map(|_| REDACTED))
+            .field("store", &self.store)
+            .finish()
+    }
+}
+
+impl Session {
+    pub fn new(
+        client: ChinaUnicomClient,
+        token_online: impl Into<String>,
+        app_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            token_online: token_online.into(),
+            app_id: app_id.into(),
+            cookie: None,
+            store: None,
+        }
+    }
+
+    // 由登录或 online() 的结果创建会话
+    pub fn from_online(
+        client: ChinaUnicomClient,
+        app_id: impl Into<String>,
+        resp: OnlineResp,
+    ) -> Result<Self> {
+        Self::new(client, resp.online_token, app_id).with_cookie(resp.cookie)
+    }
+
+    // 从凭据存储中恢复会话, 尚未保存过凭据时返回 None
+    pub fn from_store(
+        client: ChinaUnicomClient,
+        store: Arc<dyn CredentialStore>,
+    ) -> Result<Option<Self>> {
+        let Some(credentials) = store.load()? else {
+            return Ok(None);
+        };
+        let mut session = Self::new(client, credentials.token_online, credentials.app_id);
+        if let Some(cookie) = credentials.cookie {
+            session = session.with_cookie(cookie)?;
+        }
+        Ok(Some(session.with_store(store)))
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn CredentialStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    // 使用已有的 cookie, 首次查询时不必先调用 online()
+    // 配置了 cookie jar 时同时导入其中, cookie 无法导入时返回 Error::Store
+    pub fn with_cookie(mut self, cookie: impl Into<String>) -> Result<Self> {
+        let cookie = cookie.into();
+        if let Some(jar) = self.client.cookie_jar() {
+            jar.insert_cookie_header(&cookie, self.client.base_url())?;
+        }
+        self.cookie = Some(cookie);
+        Ok(self)
+    }
+
+    pub fn client(&self) -> &ChinaUnicomClient {
+        &self.client
+    }
+
+    // 当前的 token_online, 每次刷新后都会轮换, 需要持久化供下次启动使用
+    pub fn online_token(&self) -> &str {
+        &self.token_online
+    }
+
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    pub fn cookie(&self) -> Option<&str> {
+        self.cookie.as_deref()
+    }
+
+    pub fn credentials(&self) -> Credentials {
+        Credentials {
+            token_online: self.token_online.clone(),
+            app_id: self.app_id.clone(),
+            cookie: self.cookie.clone(),
+        }
+    }
+
+    // 调用 online() 获取新的 cookie, 并轮换 token_online,
+    // 旧的 token_online 随即失效, 因此设置了存储时立即写入
+    pub async fn refresh(&mut self) -> Result<OnlineResp> {
+        let resp = self.client.online(&self.token_online, &self.app_id).await?;
+        self.token_online.clone_from(&resp.online_token);
+        self.cookie = Some(resp.cookie.clone());
+        if let Some(store) = &self.store {
+            store.save(&self.credentials())?;
+        }
+        if let Some(jar) = self.client.cookie_jar() {
+            jar.persist()?;
+        }
+        Ok(resp)
+    }
+
+    // 查询余量, 登录状态失效时自动刷新 cookie 并重试一次
+    pub async fn query(&mut self) -> Result<ChinaUnicomData> {
+        let refreshed = !self.has_cookie();
+        if refreshed {
+            self.refresh().await?;
+        }
+
+        match self.query_once().await {
+            Err(e) if e.is_session_expired() && !refreshed => {
+                self.refresh().await?;
+                self.query_once().await
+            }
+            result => result,
+        }
+    }
+
+    fn has_cookie(&self) -> bool {
+        match self.client.cookie_jar() {
+            Some(jar) => jar.has_ecs_cookies(),
+            None => self.cookie.is_some(),
+        }
+    }
+
+    async fn query_once(&self) -> Result<ChinaUnicomData> {
+        match (self.client.cookie_jar(), &self.cookie) {
+            (Some(_), _) => self.client.query().await,
+            (None, Some(cookie)) => self.client.query_china_unicom_data(cookie).await,
+            (None, None) => Err(Error::MissingCookie("ecs_token, ecs_acc".to_string())),
+        }
+    }
+}
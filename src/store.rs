@@ -0,0 +1,199 @@
+use std::{
+    fmt::{self, Debug},
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::secret::REDACTED;
+
+// 登录凭据, token_online 与 cookie 在每次 online() 后都会轮换
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credentials {
+    pub token_online: String,
+    pub app_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookie: Option<String>,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("token_online", &REDACTED)
+            .field("app_id", &self.app_id)
+            .field("cookie", &self.cookie.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+// 凭据的持久化方式, 会话在每次成功刷新后写入
+pub trait CredentialStore: Debug + Send + Sync {
+    // 尚未保存过凭据时返回 None
+    fn load(&self) -> Result<Option<Credentials>>;
+
+    fn save(&self, credentials: &Credentials) -> Result<()>;
+}
+
+// 以 toml 文件保存凭据, 写入时先写临时文件再重命名, 权限为 0600
+#[derive(Debug, Clone)]
+pub struct FileCredentialStore {
+    path: PathBuf,
+}
+
+impl FileCredentialStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl CredentialStore for FileCredentialStore {
+    fn load(&self) -> Result<Option<Credentials>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let credentials = toml::from_str(&content)
+            .map_err(|e| Error::Store(format!("无法解析凭据文件 {}: {e}", self.path.display())))?;
+        Ok(Some(credentials))
+    }
+
+    fn save(&self, credentials: &Credentials) -> Result<()> {
+        let content = toml::to_string(credentials)
+            .map_err(|e| Error::Store(format!("无法序列化凭据: {e}")))?;
+        write_atomic(&self.path, content.as_bytes())
+    }
+}
+
+// 先写入同目录下的临时文件, 再重命名覆盖, 避免写入中途退出导致凭据损坏
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = open_private(&tmp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn open_private(path: &Path) -> Result<File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // 临时文件可能已存在且权限更宽松, mode 只在新建时生效
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn open_private(path: &Path) -> Result<File> {
+    Ok(OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // 每个测试使用独立的临时目录, 避免并行运行时互相覆盖
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("china_unicom_rs-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn credentials(cookie: Option<&str>) -> Credentials {
+        Credentials {
+            token_online: "token".to_string(),
+            app_id: "app".to_string(),
+            cookie: cookie.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn load_without_file_is_none() {
+        let store = FileCredentialStore::new(temp_dir("store-missing").join("credentials.toml"));
+        assert_eq!(store.load().unwrap(), None);
+    }
+
+    #[test]
+    fn load_save_load_round_trip() {
+        let store = FileCredentialStore::new(temp_dir("store-round-trip").join("credentials.toml"));
+
+        store.save(&credentials(None)).unwrap();
+        assert_eq!(store.load().unwrap(), Some(credentials(None)));
+
+        let loaded = store.load().unwrap().unwrap();
+        let updated = Credentials {
+            cookie: Some("ecs_token=t; ecs_acc=a".to_string()),
+            ..loaded
+        };
+        store.save(&updated).unwrap();
+        assert_eq!(store.load().unwrap(), Some(updated));
+    }
+
+    #[test]
+    fn invalid_file_is_store_error() {
+        let path = temp_dir("store-invalid").join("credentials.toml");
+        fs::write(&path, "token_online = ").unwrap();
+
+        let err = FileCredentialStore::new(path).load().unwrap_err();
+        assert!(matches!(err, Error::Store(_)), "{err:?}");
+    }
+
+    #[test]
+    fn write_atomic_replaces_file_via_temp_file() {
+        let path = temp_dir("store-atomic").join("credentials.toml");
+        fs::write(&path, "old").unwrap();
+        // 上次写入中途退出时残留的临时文件
+        let tmp_path = path.with_file_name("credentials.toml.tmp");
+        fs::write(&tmp_path, "stale content that is longer").unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!tmp_path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_dir("store-mode").join("credentials.toml");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let tmp_path = path.with_file_name("credentials.toml.tmp");
+        fs::write(&tmp_path, "").unwrap();
+        fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        FileCredentialStore::new(&path)
+            .save(&credentials(Some("ecs_token=t; ecs_acc=a")))
+            .unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::amount::DataAmount;
+
+// 超出套餐后的单价(元), 用于估算超出费用, 实际费用以账单为准
+// 默认值为联通常见的标准资费, 套餐不同时请自行设置
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tariff {
+    // 每 GB 流量
+    pub flow_per_gb: f64,
+    // 每分钟通话
+    pub voice_per_minute: f64,
+    // 每条短信
+    pub sms_per_message: f64,
+}
+
+impl Default for Tariff {
+    fn default() -> Self {
+        Self {
+            flow_per_gb: 5.0,
+            voice_per_minute: 0.15,
+            sms_per_message: 0.1,
+        }
+    }
+}
+
+impl Tariff {
+    pub fn flow_cost(&self, flow: DataAmount) -> f64 {
+        flow.as_gb().max(0.0) * self.flow_per_gb
+    }
+
+    pub fn voice_cost(&self, minutes: i64) -> f64 {
+        minutes.max(0) as f64 * self.voice_per_minute
+    }
+
+    pub fn sms_cost(&self, messages: i64) -> f64 {
+        messages.max(0) as f64 * self.sms_per_message
+    }
+}
@@ -0,0 +1,1893 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+
+use crate::amount::DataAmount;
+use crate::cycle::BillingCycle;
+use crate::data::{ChinaUnicomData, PackageUsage};
+use crate::delta::{PackageDelta, UsageDelta};
+use crate::error::{Error, Result};
+use crate::locale::{Locale, Word};
+use crate::quota::{Quantity, Quota};
+use crate::tariff::Tariff;
+use crate::DATETIME_FORMAT;
+
+// 预先解析的格式模板, 占位符写作 [流量通用余量], 字面量 [ 写作 [[
+// 编译时检查占位符是否存在, 渲染时只计算模板中用到的值
+//
+// 除占位符外还支持:
+// - [流量定向余量?无定向流量]: 值为 0 或没有值时输出 ? 后的文本
+// - [#非零 流量定向总量]..[#否则]..[/非零]: 值不为 0 时输出前一段, 否则输出 [#否则] 之后的部分
+// - [#有限 X]..[/有限]、[#无限 X]..[/无限]: 按总量是否无限选择输出
+// - [#流量包]..[/流量包]: 对每个流量包重复输出, 其中可使用 [包名称] 等占位符, 通话包、短信包同理
+// - [流量通用余量|MB|1]: | 后为过滤器, 可指定单位、小数位数、百分比(%)或只输出数值(raw)
+//
+// 占位符与块也可以写作英文别名, 如 {flow.general.remaining}、{#nonzero flow.limited.total}..{/nonzero}
+// 两种写法可以混用; {..} 中不是已知的占位符或块时原样输出, 如 JSON 或 {注意}
+// 字面量 [ 与 { 也可以写作 \[、\{
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+    tariff: Tariff,
+    locale: Locale,
+    progress_bar: ProgressBar,
+    cycle: BillingCycle,
+}
+
+// [流量通用进度条] 等占位符的样式, 如 ▓▓▓▓▓▓▓░░░ 67%
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgressBar {
+    // 进度条的字符数
+    pub width: usize,
+    // 已用部分的字符
+    pub filled: char,
+    // 剩余部分的字符
+    pub empty: char,
+    // 是否在进度条后输出百分比
+    pub show_percent: bool,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            filled: '▓',
+            empty: '░',
+            show_percent: true,
+        }
+    }
+}
+
+impl ProgressBar {
+    // percent 为已用百分比, 超过 100 时进度条填满, 百分比照常输出
+    pub fn render(&self, percent: f64) -> String {
+        let filled =
+            ((percent / 100.0 * self.width as f64).round().max(0.0) as usize).min(self.width);
+        let mut bar: String = std::iter::repeat_n(self.filled, filled)
+            .chain(std::iter::repeat_n(self.empty, self.width - filled))
+            .collect();
+        if self.show_percent {
+            bar.push_str(&format!(" {percent:.0}%"));
+        }
+        bar
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Field {
+        field: Field,
+        filters: Filters,
+        fallback: Option<String>,
+    },
+    If {
+        condition: Condition,
+        field: Field,
+        then: Vec<Segment>,
+        otherwise: Vec<Segment>,
+    },
+    Each {
+        resource: Resource,
+        body: Vec<Segment>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    NonZero,
+    Limited,
+    Unlimited,
+}
+
+impl Condition {
+    const ALL: [Self; 3] = [Self::NonZero, Self::Limited, Self::Unlimited];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::NonZero => "非零",
+            Self::Limited => "有限",
+            Self::Unlimited => "无限",
+        }
+    }
+
+    fn alias(self) -> &'static str {
+        match self {
+            Self::NonZero => "nonzero",
+            Self::Limited => "limited",
+            Self::Unlimited => "unlimited",
+        }
+    }
+
+    fn lookup(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|condition| condition.name() == name || condition.alias() == name)
+    }
+
+    fn test(self, value: &Value) -> bool {
+        match self {
+            Self::NonZero => !value.is_zero(),
+            Self::Limited => !value.is_unlimited(),
+            Self::Unlimited => value.is_unlimited(),
+        }
+    }
+}
+
+// 资源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resource {
+    Flow,
+    Voice,
+    Sms,
+}
+
+// 统计范围, 免费与收费只用于流量用量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Sum,
+    Limited,
+    General,
+    Free,
+    Paid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Measure {
+    Total,
+    Used,
+    Remaining,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Time,
+    PackageName,
+    Usage(Resource, Scope, Measure),
+    // 用量或余量占总量的百分比
+    Percent(Resource, Scope, Measure),
+    // 已用比例的进度条
+    Bar(Resource, Scope),
+    ThrottleState,
+    HighSpeedRemaining,
+    CarryOver(Measure),
+    CarryOverExpiry,
+    CarryOverDaysLeft,
+    Exceed(Resource),
+    OverageCost,
+    IntervalDuration,
+    Interval(Resource, Scope),
+    IntervalCarryOver,
+    IntervalExceed(Resource),
+    IntervalThrottleChange,
+    Package(PackageField),
+}
+
+// 循环中当前资源包的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PackageField {
+    Name,
+    Kind,
+    Total,
+    Used,
+    Remaining,
+    Expiry,
+    IntervalUsed,
+}
+
+impl PackageField {
+    const ALL: [Self; 7] = [
+        Self::Name,
+        Self::Kind,
+        Self::Total,
+        Self::Used,
+        Self::Remaining,
+        Self::Expiry,
+        Self::IntervalUsed,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Name => "包名称",
+            Self::Kind => "包类型",
+            Self::Total => "包总量",
+            Self::Used => "包用量",
+            Self::Remaining => "包余量",
+            Self::Expiry => "包到期",
+            Self::IntervalUsed => "区间包用量",
+        }
+    }
+
+    fn alias(self) -> &'static str {
+        match self {
+            Self::Name => "package.name",
+            Self::Kind => "package.kind",
+            Self::Total => "package.total",
+            Self::Used => "package.used",
+            Self::Remaining => "package.remaining",
+            Self::Expiry => "package.expiry",
+            Self::IntervalUsed => "package.interval_used",
+        }
+    }
+}
+
+const RESOURCES: [Resource; 3] = [Resource::Flow, Resource::Voice, Resource::Sms];
+const MEASURES: [Measure; 3] = [Measure::Total, Measure::Used, Measure::Remaining];
+
+impl Resource {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Flow => "流量",
+            Self::Voice => "通话",
+            Self::Sms => "短信",
+        }
+    }
+
+    fn alias(self) -> &'static str {
+        match self {
+            Self::Flow => "flow",
+            Self::Voice => "voice",
+            Self::Sms => "sms",
+        }
+    }
+
+    // 该资源可用的统计范围
+    fn scopes(self) -> &'static [Scope] {
+        match self {
+            Self::Flow => &[
+                Scope::Sum,
+                Scope::Limited,
+                Scope::General,
+                Scope::Free,
+                Scope::Paid,
+            ],
+            Self::Voice | Self::Sms => &[Scope::Sum, Scope::Limited, Scope::General],
+        }
+    }
+
+    // 循环块的名称, 如 流量包
+    fn package_block(self) -> String {
+        format!("{}包", self.name())
+    }
+
+    // 循环块的英文别名, 如 flow.packages
+    fn package_block_alias(self) -> String {
+        format!("{}.packages", self.alias())
+    }
+}
+
+impl Scope {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sum => "",
+            Self::Limited => "定向",
+            Self::General => "通用",
+            Self::Free => "免费",
+            Self::Paid => "收费",
+        }
+    }
+
+    fn alias(self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Limited => "limited",
+            Self::General => "general",
+            Self::Free => "free",
+            Self::Paid => "paid",
+        }
+    }
+
+    // 免费、收费只有用量
+    fn has_measure(self, measure: Measure) -> bool {
+        !matches!(self, Self::Free | Self::Paid) || measure == Measure::Used
+    }
+}
+
+impl Measure {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Total => "总量",
+            Self::Used => "用量",
+            Self::Remaining => "余量",
+        }
+    }
+
+    fn alias(self) -> &'static str {
+        match self {
+            Self::Total => "total",
+            Self::Used => "used",
+            Self::Remaining => "remaining",
+        }
+    }
+}
+
+// 总量写作 "总量" 而不是 "总总量"
+fn usage_name(scope: Scope, measure: Measure) -> String {
+    let prefix = match (scope, measure) {
+        (Scope::Sum, Measure::Total) => "",
+        (Scope::Sum, _) => "总",
+        (scope, _) => scope.name(),
+    };
+    format!("{prefix}{}", measure.name())
+}
+
+impl Field {
+    fn all() -> Vec<Self> {
+        let mut fields = vec![Self::Time, Self::PackageName];
+        for resource in RESOURCES {
+            for &scope in resource.scopes() {
+                for measure in MEASURES {
+                    if scope.has_measure(measure) {
+                        fields.push(Self::Usage(resource, scope, measure));
+                    }
+                }
+            }
+        }
+        // 免费、收费流量没有总量, 不计算百分比
+        for resource in RESOURCES {
+            for &scope in resource.scopes() {
+                if scope.has_measure(Measure::Total) {
+                    fields.extend([
+                        Self::Percent(resource, scope, Measure::Used),
+                        Self::Percent(resource, scope, Measure::Remaining),
+                        Self::Bar(resource, scope),
+                    ]);
+                }
+            }
+        }
+        fields.extend([Self::ThrottleState, Self::HighSpeedRemaining]);
+        fields.extend(MEASURES.map(Self::CarryOver));
+        fields.extend([Self::CarryOverExpiry, Self::CarryOverDaysLeft]);
+        fields.extend(RESOURCES.map(Self::Exceed));
+        fields.extend([Self::OverageCost, Self::IntervalDuration]);
+        for resource in RESOURCES {
+            for &scope in resource.scopes() {
+                fields.push(Self::Interval(resource, scope));
+            }
+        }
+        fields.push(Self::IntervalCarryOver);
+        fields.extend(RESOURCES.map(Self::IntervalExceed));
+        fields.push(Self::IntervalThrottleChange);
+        fields.extend(PackageField::ALL.map(Self::Package));
+        fields
+    }
+
+    fn lookup(name: &str) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|field| field.name() == name || field.alias() == name)
+    }
+
+    // 模板中的占位符名称(不含方括号)
+    fn name(self) -> String {
+        match self {
+            Self::Time => "时间".to_string(),
+            Self::PackageName => "套餐名称".to_string(),
+            Self::Usage(resource, scope, measure) => {
+                format!("{}{}", resource.name(), usage_name(scope, measure))
+            }
+            Self::Percent(resource, scope, measure) => {
+                format!("{}{}百分比", resource.name(), usage_name(scope, measure))
+            }
+            Self::Bar(resource, scope) => format!("{}{}进度条", resource.name(), scope.name()),
+            Self::ThrottleState => "限速状态".to_string(),
+            Self::HighSpeedRemaining => "高速流量余量".to_string(),
+            Self::CarryOver(measure) => format!("结转流量{}", measure.name()),
+            Self::CarryOverExpiry => "结转流量到期".to_string(),
+            Self::CarryOverDaysLeft => "结转流量剩余天数".to_string(),
+            Self::Exceed(resource) => format!("{}超出", resource.name()),
+            Self::OverageCost => "超出费用".to_string(),
+            Self::IntervalDuration => "区间时长".to_string(),
+            Self::Interval(resource, scope) => {
+                format!(
+                    "区间{}{}",
+                    resource.name(),
+                    usage_name(scope, Measure::Used)
+                )
+            }
+            Self::IntervalCarryOver => "区间结转流量用量".to_string(),
+            Self::IntervalExceed(resource) => format!("区间{}超出", resource.name()),
+            Self::IntervalThrottleChange => "区间限速变化".to_string(),
+            Self::Package(field) => field.name().to_string(),
+        }
+    }
+
+    // 英文别名, 如 flow.general.remaining
+    fn alias(self) -> String {
+        match self {
+            Self::Time => "time".to_string(),
+            Self::PackageName => "plan.name".to_string(),
+            Self::Usage(resource, scope, measure) => {
+                format!("{}.{}.{}", resource.alias(), scope.alias(), measure.alias())
+            }
+            Self::Percent(resource, scope, measure) => {
+                format!(
+                    "{}.{}.{}_percent",
+                    resource.alias(),
+                    scope.alias(),
+                    measure.alias()
+                )
+            }
+            Self::Bar(resource, scope) => format!("{}.{}.bar", resource.alias(), scope.alias()),
+            Self::ThrottleState => "throttle.state".to_string(),
+            Self::HighSpeedRemaining => "throttle.high_speed_remaining".to_string(),
+            Self::CarryOver(measure) => format!("carry_over.{}", measure.alias()),
+            Self::CarryOverExpiry => "carry_over.expiry".to_string(),
+            Self::CarryOverDaysLeft => "carry_over.days_left".to_string(),
+            Self::Exceed(resource) => format!("{}.exceed", resource.alias()),
+            Self::OverageCost => "overage.cost".to_string(),
+            Self::IntervalDuration => "interval.duration".to_string(),
+            Self::Interval(resource, scope) => {
+                format!("interval.{}.{}.used", resource.alias(), scope.alias())
+            }
+            Self::IntervalCarryOver => "interval.carry_over.used".to_string(),
+            Self::IntervalExceed(resource) => format!("interval.{}.exceed", resource.alias()),
+            Self::IntervalThrottleChange => "interval.throttle.change".to_string(),
+            Self::Package(field) => field.alias().to_string(),
+        }
+    }
+
+    // 是否需要上次查询的数据
+    fn is_interval(self) -> bool {
+        matches!(
+            self,
+            Self::IntervalDuration
+                | Self::Interval(..)
+                | Self::IntervalCarryOver
+                | Self::IntervalExceed(_)
+                | Self::IntervalThrottleChange
+                | Self::Package(PackageField::IntervalUsed)
+        )
+    }
+
+    // 是否只能用在资源包循环中
+    fn is_package(self) -> bool {
+        matches!(self, Self::Package(_))
+    }
+
+    // 值的类型, 资源包字段的类型取决于所在循环
+    fn kind(self, package: Option<Resource>) -> Kind {
+        match self {
+            Self::Usage(resource, ..)
+            | Self::Exceed(resource)
+            | Self::Interval(resource, _)
+            | Self::IntervalExceed(resource) => Kind::of(resource),
+            Self::HighSpeedRemaining | Self::CarryOver(_) | Self::IntervalCarryOver => Kind::Flow,
+            Self::CarryOverDaysLeft => Kind::Count,
+            Self::OverageCost => Kind::Money,
+            Self::Percent(..) => Kind::Percent,
+            Self::IntervalDuration => Kind::Duration,
+            Self::Package(
+                PackageField::Total
+                | PackageField::Used
+                | PackageField::Remaining
+                | PackageField::IntervalUsed,
+            ) => package.map_or(Kind::Text, Kind::of),
+            Self::Time
+            | Self::PackageName
+            | Self::Bar(..)
+            | Self::ThrottleState
+            | Self::CarryOverExpiry
+            | Self::IntervalThrottleChange
+            | Self::Package(_) => Kind::Text,
+        }
+    }
+
+    // % 过滤器的基准, 即同一范围的总量
+    // 免费、收费流量按总流量计算
+    fn total_field(self) -> Option<Self> {
+        match self {
+            Self::Usage(resource, scope, _) | Self::Interval(resource, scope) => {
+                let scope = match scope {
+                    Scope::Free | Scope::Paid => Scope::Sum,
+                    scope => scope,
+                };
+                Some(Self::Usage(resource, scope, Measure::Total))
+            }
+            Self::CarryOver(_) | Self::IntervalCarryOver => Some(Self::CarryOver(Measure::Total)),
+            Self::Package(
+                PackageField::Total
+                | PackageField::Used
+                | PackageField::Remaining
+                | PackageField::IntervalUsed,
+            ) => Some(Self::Package(PackageField::Total)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Flow,
+    // 通话时长, 以分钟计
+    Minutes,
+    Count,
+    Duration,
+    Money,
+    Percent,
+}
+
+impl Kind {
+    fn of(resource: Resource) -> Self {
+        match resource {
+            Resource::Flow => Self::Flow,
+            Resource::Voice => Self::Minutes,
+            Resource::Sms => Self::Count,
+        }
+    }
+}
+
+// 流量单位, 各单位之间按 1024 换算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DataUnit {
+    B,
+    Kb,
+    Mb,
+    Gb,
+    Tb,
+}
+
+impl DataUnit {
+    const ALL: [Self; 5] = [Self::B, Self::Kb, Self::Mb, Self::Gb, Self::Tb];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::B => "B",
+            Self::Kb => "KB",
+            Self::Mb => "MB",
+            Self::Gb => "GB",
+            Self::Tb => "TB",
+        }
+    }
+
+    fn convert(self, amount: DataAmount) -> f64 {
+        match self {
+            Self::B => amount.as_bytes() as f64,
+            Self::Kb => amount.as_kb() as f64,
+            Self::Mb => amount.as_mb(),
+            Self::Gb => amount.as_gb(),
+            Self::Tb => amount.as_tb(),
+        }
+    }
+
+    // B、KB 默认不保留小数
+    fn default_precision(self) -> usize {
+        match self {
+            Self::B | Self::Kb => 0,
+            _ => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    // 流量自动选择单位
+    Auto,
+    Data(DataUnit),
+    Minute,
+    Hour,
+}
+
+impl Unit {
+    fn lookup(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Self::Auto),
+            "分钟" | "min" => Some(Self::Minute),
+            "小时" | "h" => Some(Self::Hour),
+            _ => DataUnit::ALL
+                .into_iter()
+                .find(|unit| unit.name().eq_ignore_ascii_case(name))
+                .map(Self::Data),
+        }
+    }
+
+    fn accepts(self, kind: Kind) -> bool {
+        match self {
+            Self::Auto | Self::Data(_) => kind == Kind::Flow,
+            Self::Minute | Self::Hour => matches!(kind, Kind::Minutes | Kind::Duration),
+        }
+    }
+}
+
+// 占位符 | 后的过滤器
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Filters {
+    unit: Option<Unit>,
+    precision: Option<usize>,
+    // 输出占总量的百分比
+    percent: bool,
+    // 只输出数值, 不带单位
+    raw: bool,
+}
+
+impl Filters {
+    fn parse(field: Field, kind: Kind, filters: &[&str]) -> Result<Self> {
+        let mut parsed = Self::default();
+        for &filter in filters {
+            let filter = filter.trim();
+            let unsupported =
+                || Error::Format(format!("占位符 [{}] 不支持过滤器 |{filter}", field.name()));
+            if kind == Kind::Text {
+                return Err(unsupported());
+            }
+            if let Ok(precision) = filter.parse() {
+                parsed.precision = Some(precision);
+            } else if filter == "%" {
+                field.total_field().ok_or_else(unsupported)?;
+                parsed.percent = true;
+            } else if filter == "raw" {
+                parsed.raw = true;
+            } else if let Some(unit) = Unit::lookup(filter) {
+                if !unit.accepts(kind) {
+                    return Err(unsupported());
+                }
+                parsed.unit = Some(unit);
+            } else {
+                return Err(Error::Format(format!("未知的过滤器 |{filter}")));
+            }
+        }
+        if parsed.percent && parsed.unit.is_some() {
+            return Err(Error::Format(format!(
+                "占位符 [{}] 的 |% 不能与单位同时使用",
+                field.name()
+            )));
+        }
+        Ok(parsed)
+    }
+
+    // 按精度输出数值, raw 时省略单位
+    fn number(&self, number: f64, default_precision: usize, unit: &str) -> String {
+        let precision = self.precision.unwrap_or(default_precision);
+        if self.raw {
+            format!("{number:.precision$}")
+        } else {
+            format!("{number:.precision$}{unit}")
+        }
+    }
+
+    // 未指定单位时自动选择, raw 时按 KB 输出
+    fn flow(&self, amount: DataAmount) -> String {
+        match (self.unit, self.precision) {
+            (Some(Unit::Data(unit)), _) => {
+                self.number(unit.convert(amount), unit.default_precision(), unit.name())
+            }
+            _ if self.raw => self.number(amount.as_kb() as f64, 0, ""),
+            (_, Some(precision)) => format!("{amount:.precision$}"),
+            (_, None) => amount.to_string(),
+        }
+    }
+
+    fn count(&self, count: i64, unit: Word, locale: Locale) -> String {
+        match self.unit {
+            Some(Unit::Hour) => self.number(count as f64 / 60.0, 2, locale.word(Word::Hour)),
+            _ => self.number(count as f64, 0, locale.word(unit)),
+        }
+    }
+
+    // 未指定单位时按 天/小时/分钟 输出, raw 时按分钟输出
+    fn duration(&self, duration: TimeDelta, locale: Locale) -> String {
+        let seconds = duration.num_seconds() as f64;
+        match self.unit {
+            Some(Unit::Hour) => self.number(seconds / 3600.0, 2, locale.word(Word::Hour)),
+            Some(Unit::Minute) => self.number(seconds / 60.0, 0, locale.word(Word::Minute)),
+            _ if self.raw => self.number(seconds / 60.0, 0, ""),
+            _ => locale.format_duration(duration),
+        }
+    }
+}
+
+// 占位符的值, 渲染时再转为文本
+enum Value {
+    Text(String),
+    Flow(Quota<DataAmount>),
+    Count(Quota<i64>, Word),
+    Duration(TimeDelta),
+    Money(f64),
+    Percent(f64),
+    Missing,
+}
+
+impl Value {
+    // 没有值也视为 0
+    fn is_zero(&self) -> bool {
+        match self {
+            Self::Text(text) => text.is_empty(),
+            Self::Flow(quota) => quota.limit().is_some_and(|amount| amount.is_zero()),
+            Self::Count(quota, _) => quota.limit() == Some(0),
+            Self::Duration(duration) => duration.is_zero(),
+            Self::Money(money) => *money == 0.0,
+            Self::Percent(percent) => *percent == 0.0,
+            Self::Missing => true,
+        }
+    }
+
+    fn is_unlimited(&self) -> bool {
+        match self {
+            Self::Flow(quota) => quota.is_unlimited(),
+            Self::Count(quota, _) => quota.is_unlimited(),
+            _ => false,
+        }
+    }
+
+    // 有限的流量或数量, 流量以 KB 计
+    fn amount(&self) -> Option<f64> {
+        match self {
+            Self::Flow(Quota::Limited(amount)) => Some(amount.as_kb() as f64),
+            Self::Count(Quota::Limited(count), _) => Some(*count as f64),
+            _ => None,
+        }
+    }
+
+    fn render(&self, filters: &Filters, locale: Locale) -> String {
+        let quota = |quota: Quota<String>| match quota {
+            Quota::Limited(text) => text,
+            Quota::Unlimited => locale.unlimited().to_string(),
+        };
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Flow(amount) => quota(amount.map(|amount| filters.flow(amount))),
+            Self::Count(count, unit) => {
+                quota(count.map(|count| filters.count(count, *unit, locale)))
+            }
+            Self::Duration(duration) => filters.duration(*duration, locale),
+            Self::Money(money) => filters.number(*money, 2, locale.word(Word::Yuan)),
+            Self::Percent(percent) => filters.number(*percent, 0, "%"),
+            Self::Missing => locale.word(Word::Missing).to_string(),
+        }
+    }
+}
+
+// 通话、短信的单位, 流量不使用
+fn unit(resource: Resource) -> Word {
+    match resource {
+        Resource::Voice => Word::Minute,
+        Resource::Flow | Resource::Sms => Word::Message,
+    }
+}
+
+// 定向、通用、合计三个范围的总量、用量或余量
+// 合计余量的规则与 Quota::total 一致
+#[allow(clippy::too_many_arguments)]
+fn bucket<T: Quantity>(
+    scope: Scope,
+    measure: Measure,
+    sum: Quota<T>,
+    sum_used: T,
+    limit: Quota<T>,
+    limit_used: T,
+    non_limit: Quota<T>,
+    non_limit_used: T,
+) -> Option<Quota<T>> {
+    Some(match (scope, measure) {
+        (Scope::Sum, Measure::Total) => sum,
+        (Scope::Sum, Measure::Used) => Quota::Limited(sum_used),
+        (Scope::Sum, Measure::Remaining) => Quota::total(
+            limit.remaining(limit_used),
+            non_limit.remaining(non_limit_used),
+        ),
+        (Scope::Limited, Measure::Total) => limit,
+        (Scope::Limited, Measure::Used) => Quota::Limited(limit_used),
+        (Scope::Limited, Measure::Remaining) => limit.remaining(limit_used),
+        (Scope::General, Measure::Total) => non_limit,
+        (Scope::General, Measure::Used) => Quota::Limited(non_limit_used),
+        (Scope::General, Measure::Remaining) => non_limit.remaining(non_limit_used),
+        (Scope::Free | Scope::Paid, _) => return None,
+    })
+}
+
+fn usage(data: &ChinaUnicomData, resource: Resource, scope: Scope, measure: Measure) -> Value {
+    let d = data;
+    match resource {
+        Resource::Flow => {
+            let quota = match scope {
+                Scope::Free => Some(Quota::Limited(d.free_flow_used)),
+                Scope::Paid => Some(Quota::Limited(d.non_free_flow_used)),
+                _ => bucket(
+                    scope,
+                    measure,
+                    d.sum_flow,
+                    d.sum_flow_used,
+                    d.limit_flow,
+                    d.limit_flow_used,
+                    d.non_limit_flow,
+                    d.non_limit_flow_used,
+                ),
+            };
+            quota.map_or(Value::Missing, Value::Flow)
+        }
+        Resource::Voice => bucket(
+            scope,
+            measure,
+            d.sum_voice,
+            d.sum_voice_used,
+            d.limit_voice,
+            d.limit_voice_used,
+            d.non_limit_voice,
+            d.non_limit_voice_used,
+        )
+        .map_or(Value::Missing, |quota| Value::Count(quota, unit(resource))),
+        Resource::Sms => bucket(
+            scope,
+            measure,
+            d.sum_sms,
+            d.sum_sms_used,
+            d.limit_sms,
+            d.limit_sms_used,
+            d.non_limit_sms,
+            d.non_limit_sms_used,
+        )
+        .map_or(Value::Missing, |quota| Value::Count(quota, unit(resource))),
+    }
+}
+
+fn interval(delta: &UsageDelta, resource: Resource, scope: Scope) -> Value {
+    match resource {
+        Resource::Flow => Value::Flow(Quota::Limited(match scope {
+            Scope::Sum => delta.sum_flow_used,
+            Scope::Limited => delta.limit_flow_used,
+            Scope::General => delta.non_limit_flow_used,
+            Scope::Free => delta.free_flow_used,
+            Scope::Paid => delta.non_free_flow_used,
+        })),
+        Resource::Voice | Resource::Sms => {
+            let (sum, limit, non_limit) = if resource == Resource::Voice {
+                (
+                    delta.sum_voice_used,
+                    delta.limit_voice_used,
+                    delta.non_limit_voice_used,
+                )
+            } else {
+                (
+                    delta.sum_sms_used,
+                    delta.limit_sms_used,
+                    delta.non_limit_sms_used,
+                )
+            };
+            let count = match scope {
+                Scope::Limited => limit,
+                Scope::General => non_limit,
+                _ => sum,
+            };
+            Value::Count(Quota::Limited(count), unit(resource))
+        }
+    }
+}
+
+// 渲染时的数据, 循环中 package 为当前资源包
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    data: &'a ChinaUnicomData,
+    delta: Option<&'a UsageDelta>,
+    package: Option<PackageRef<'a>>,
+}
+
+// 资源包及其区间用量
+#[derive(Clone, Copy)]
+enum PackageRef<'a> {
+    Flow(
+        &'a PackageUsage<DataAmount>,
+        Option<&'a PackageDelta<DataAmount>>,
+    ),
+    Voice(&'a PackageUsage<i64>, Option<&'a PackageDelta<i64>>),
+    Sms(&'a PackageUsage<i64>, Option<&'a PackageDelta<i64>>),
+}
+
+impl<'a> Context<'a> {
+    // 资源包及与之对应的区间用量, UsageDelta 中的资源包与本次查询的顺序一致
+    fn packages(&self, resource: Resource) -> Vec<PackageRef<'a>> {
+        let Self { data, delta, .. } = *self;
+        match resource {
+            Resource::Flow => data
+                .flow_packages
+                .iter()
+                .enumerate()
+                .map(|(i, p)| PackageRef::Flow(p, delta.and_then(|d| d.flow_packages.get(i))))
+                .collect(),
+            Resource::Voice => data
+                .voice_packages
+                .iter()
+                .enumerate()
+                .map(|(i, p)| PackageRef::Voice(p, delta.and_then(|d| d.voice_packages.get(i))))
+                .collect(),
+            Resource::Sms => data
+                .sms_packages
+                .iter()
+                .enumerate()
+                .map(|(i, p)| PackageRef::Sms(p, delta.and_then(|d| d.sms_packages.get(i))))
+                .collect(),
+        }
+    }
+}
+
+fn package_value<T: Copy>(
+    package: &PackageUsage<T>,
+    delta: Option<&PackageDelta<T>>,
+    field: PackageField,
+    locale: Locale,
+    amount: impl Fn(Quota<T>) -> Value,
+) -> Value {
+    match field {
+        PackageField::Name => text(&package.fee_policy_name),
+        PackageField::Kind => text(locale.word(if package.limited {
+            Word::Limited
+        } else {
+            Word::General
+        })),
+        PackageField::Total => amount(package.total),
+        PackageField::Used => amount(Quota::Limited(package.used)),
+        PackageField::Remaining => amount(package.remaining),
+        PackageField::Expiry => optional(package.end_date, text),
+        PackageField::IntervalUsed => {
+            optional(delta.map(|delta| Quota::Limited(delta.used)), amount)
+        }
+    }
+}
+
+impl PackageRef<'_> {
+    fn value(self, field: PackageField, locale: Locale) -> Value {
+        let count = |unit| move |count| Value::Count(count, unit);
+        match self {
+            Self::Flow(package, delta) => package_value(package, delta, field, locale, Value::Flow),
+            Self::Voice(package, delta) => {
+                package_value(package, delta, field, locale, count(unit(Resource::Voice)))
+            }
+            Self::Sms(package, delta) => {
+                package_value(package, delta, field, locale, count(unit(Resource::Sms)))
+            }
+        }
+    }
+}
+
+fn optional<T>(value: Option<T>, f: impl FnOnce(T) -> Value) -> Value {
+    value.map_or(Value::Missing, f)
+}
+
+fn text(text: impl Display) -> Value {
+    Value::Text(text.to_string())
+}
+
+impl Template {
+    pub fn compile(source: &str) -> Result<Self> {
+        Ok(Self {
+            segments: parse(source)?,
+            tariff: Tariff::default(),
+            locale: Locale::default(),
+            progress_bar: ProgressBar::default(),
+            cycle: BillingCycle::default(),
+        })
+    }
+
+    // 估算 [超出费用] 时使用的单价
+    pub fn tariff(mut self, tariff: Tariff) -> Self {
+        self.tariff = tariff;
+        self
+    }
+
+    // 输出使用的语言, 默认为简体中文
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    // [流量通用进度条] 等占位符的样式
+    pub fn progress_bar(mut self, progress_bar: ProgressBar) -> Self {
+        self.progress_bar = progress_bar;
+        self
+    }
+
+    // render_with_last 判断账期是否重置时使用的账期, 默认每月 1 日重置
+    pub fn cycle(mut self, cycle: BillingCycle) -> Self {
+        self.cycle = cycle;
+        self
+    }
+
+    // 模板是否使用了需要上次查询数据的区间占位符
+    pub fn needs_delta(&self) -> bool {
+        needs_delta(&self.segments)
+    }
+
+    // 模板中有区间占位符时返回 Error::Format
+    pub fn render(&self, data: &ChinaUnicomData) -> Result<String> {
+        self.render_inner(data, None)
+    }
+
+    pub fn render_with_last(
+        &self,
+        data: &ChinaUnicomData,
+        last: &ChinaUnicomData,
+    ) -> Result<String> {
+        let delta = UsageDelta::between_in_cycle(last, data, self.cycle);
+        self.render_inner(data, Some(&delta))
+    }
+
+    pub fn render_with_delta(&self, data: &ChinaUnicomData, delta: &UsageDelta) -> Result<String> {
+        self.render_inner(data, Some(delta))
+    }
+
+    fn render_inner(&self, data: &ChinaUnicomData, delta: Option<&UsageDelta>) -> Result<String> {
+        let mut output = String::new();
+        let context = Context {
+            data,
+            delta,
+            package: None,
+        };
+        self.render_segments(&self.segments, context, &mut output)?;
+        Ok(output)
+    }
+
+    fn render_segments(
+        &self,
+        segments: &[Segment],
+        context: Context<'_>,
+        output: &mut String,
+    ) -> Result<()> {
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Field {
+                    field,
+                    filters,
+                    fallback,
+                } => {
+                    let value = if filters.percent {
+                        self.percentage(*field, context)?
+                    } else {
+                        self.value(*field, context)?
+                    };
+                    match fallback {
+                        Some(fallback) if value.is_zero() => output.push_str(fallback),
+                        _ => output.push_str(&value.render(filters, self.locale)),
+                    }
+                }
+                Segment::If {
+                    condition,
+                    field,
+                    then,
+                    otherwise,
+                } => {
+                    let value = self.value(*field, context)?;
+                    let branch = if condition.test(&value) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    self.render_segments(branch, context, output)?;
+                }
+                Segment::Each { resource, body } => {
+                    for package in context.packages(*resource) {
+                        let context = Context {
+                            package: Some(package),
+                            ..context
+                        };
+                        self.render_segments(body, context, output)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 占同一范围总量的百分比, 总量无限或为 0 时没有值
+    fn percentage(&self, field: Field, context: Context<'_>) -> Result<Value> {
+        let value = self.value(field, context)?;
+        let total = match field.total_field() {
+            Some(total) => self.value(total, context)?,
+            None => Value::Missing,
+        };
+        Ok(match (value.amount(), total.amount()) {
+            (Some(value), Some(total)) if total > 0.0 => Value::Percent(value / total * 100.0),
+            _ => Value::Missing,
+        })
+    }
+
+    fn value(&self, field: Field, context: Context<'_>) -> Result<Value> {
+        let data = context.data;
+        let delta = match (field.is_interval(), context.delta) {
+            (false, _) => None,
+            (true, Some(delta)) => Some(delta),
+            (true, None) => {
+                return Err(Error::Format(format!(
+                    "占位符 [{}] 需要上次查询的数据",
+                    field.name()
+                )))
+            }
+        };
+
+        Ok(match (field, delta) {
+            (Field::Time, _) => text(data.time.format(DATETIME_FORMAT)),
+            (Field::PackageName, _) => text(&data.package_name),
+            (Field::Usage(resource, scope, measure), _) => usage(data, resource, scope, measure),
+            (Field::Percent(resource, scope, measure), _) => {
+                self.percentage(Field::Usage(resource, scope, measure), context)?
+            }
+            (Field::Bar(resource, scope), _) => {
+                if usage(data, resource, scope, Measure::Total).is_unlimited() {
+                    text(self.locale.unlimited())
+                } else {
+                    match self.percentage(Field::Usage(resource, scope, Measure::Used), context)? {
+                        Value::Percent(percent) => text(self.progress_bar.render(percent)),
+                        value => value,
+                    }
+                }
+            }
+            (Field::ThrottleState, _) => text(self.locale.word(if data.throttled {
+                Word::Throttled
+            } else {
+                Word::NotThrottled
+            })),
+            (Field::HighSpeedRemaining, _) => optional(data.high_speed_flow_remaining, |amount| {
+                Value::Flow(Quota::Limited(amount))
+            }),
+            (Field::CarryOver(measure), _) => Value::Flow(Quota::Limited(match measure {
+                Measure::Total => data.carry_over_flow,
+                Measure::Used => data.carry_over_flow_used,
+                Measure::Remaining => data.carry_over_flow_remaining(),
+            })),
+            (Field::CarryOverExpiry, _) => optional(data.carry_over_flow_expiry, text),
+            (Field::CarryOverDaysLeft, _) => optional(data.carry_over_flow_days_left(), |days| {
+                Value::Count(Quota::Limited(days), Word::Day)
+            }),
+            (Field::Exceed(resource), _) => match resource {
+                Resource::Flow => Value::Flow(Quota::Limited(data.flow_exceed)),
+                Resource::Voice => Value::Count(Quota::Limited(data.voice_exceed), unit(resource)),
+                Resource::Sms => Value::Count(Quota::Limited(data.sms_exceed), unit(resource)),
+            },
+            (Field::OverageCost, _) => Value::Money(data.overage_cost(&self.tariff)),
+            (Field::IntervalDuration, Some(delta)) => Value::Duration(delta.duration),
+            (Field::Interval(resource, scope), Some(delta)) => interval(delta, resource, scope),
+            (Field::IntervalCarryOver, Some(delta)) => {
+                Value::Flow(Quota::Limited(delta.carry_over_flow_used))
+            }
+            (Field::IntervalExceed(resource), Some(delta)) => match resource {
+                Resource::Flow => Value::Flow(Quota::Limited(delta.flow_exceed)),
+                Resource::Voice => Value::Count(Quota::Limited(delta.voice_exceed), unit(resource)),
+                Resource::Sms => Value::Count(Quota::Limited(delta.sms_exceed), unit(resource)),
+            },
+            (Field::IntervalThrottleChange, Some(delta)) => {
+                optional(delta.throttle_change, |change| {
+                    text(change.as_str(self.locale))
+                })
+            }
+            // 解析时已保证只出现在循环中
+            (Field::Package(field), _) => {
+                optional(context.package, |package| package.value(field, self.locale))
+            }
+            (_, None) => unreachable!("区间占位符已在上面检查"),
+        })
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        Self::compile(source)
+    }
+}
+
+fn needs_delta(segments: &[Segment]) -> bool {
+    segments.iter().any(|segment| match segment {
+        Segment::Text(_) => false,
+        Segment::Field { field, .. } => field.is_interval(),
+        Segment::If {
+            field,
+            then,
+            otherwise,
+            ..
+        } => field.is_interval() || needs_delta(then) || needs_delta(otherwise),
+        Segment::Each { body, .. } => needs_delta(body),
+    })
+}
+
+fn parse(source: &str) -> Result<Vec<Segment>> {
+    let mut parser = Parser { rest: source };
+    let (segments, _) = parser.parse_block(None, None)?;
+    Ok(segments)
+}
+
+enum Token<'a> {
+    Text(String),
+    // 方括号中的内容
+    Tag(&'a str),
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    // 占位符写作 [..] 或 {..}, 连续两个左括号或 \[、\]、\{、\} 为字面量
+    // {..} 只有内容是已知的占位符或块时才视为标签, 其余(如 JSON、{注意})原样输出
+    fn next_token(&mut self) -> Result<Option<Token<'a>>> {
+        let mut text = String::new();
+        while let Some(start) = self.rest.find(['[', '{', '\\']) {
+            text.push_str(&self.rest[..start]);
+            self.rest = &self.rest[start..];
+            if let Some(after) = self.rest.strip_prefix('\\') {
+                match after.chars().next() {
+                    Some(escaped @ ('[' | ']' | '{' | '}')) => {
+                        text.push(escaped);
+                        self.rest = &after[1..];
+                    }
+                    _ => {
+                        text.push('\\');
+                        self.rest = after;
+                    }
+                }
+                continue;
+            }
+
+            let (open, close) = if self.rest.starts_with('[') {
+                ('[', ']')
+            } else {
+                ('{', '}')
+            };
+            if let Some(after) = self.rest[1..].strip_prefix(open) {
+                text.push(open);
+                self.rest = after;
+                continue;
+            }
+
+            let end = self.rest.find(close);
+            if open == '{' && !end.is_some_and(|end| is_known_tag(&self.rest[1..end])) {
+                text.push(open);
+                self.rest = &self.rest[1..];
+                continue;
+            }
+            if !text.is_empty() {
+                return Ok(Some(Token::Text(text)));
+            }
+
+            let end =
+                end.ok_or_else(|| Error::Format(format!("占位符缺少 {close}: {}", self.rest)))?;
+            let tag = &self.rest[1..end];
+            self.rest = &self.rest[end + 1..];
+            return Ok(Some(Token::Tag(tag)));
+        }
+
+        text.push_str(self.rest);
+        self.rest = "";
+        Ok((!text.is_empty()).then_some(Token::Text(text)))
+    }
+
+    // 解析到 [/closing] 为止, 返回的 bool 表示是否遇到了 [#否则]
+    fn parse_block(
+        &mut self,
+        closing: Option<&str>,
+        package: Option<Resource>,
+    ) -> Result<(Vec<Segment>, bool)> {
+        self.parse_until(closing, package, false)
+    }
+
+    fn parse_until(
+        &mut self,
+        closing: Option<&str>,
+        package: Option<Resource>,
+        allow_else: bool,
+    ) -> Result<(Vec<Segment>, bool)> {
+        let mut segments = Vec::new();
+        while let Some(token) = self.next_token()? {
+            let tag = match token {
+                Token::Text(text) => {
+                    segments.push(Segment::Text(text));
+                    continue;
+                }
+                Token::Tag(tag) => tag,
+            };
+
+            if let Some(name) = tag.strip_prefix('/') {
+                if closing == Some(name) {
+                    return Ok((segments, false));
+                }
+                return Err(Error::Format(format!("[/{name}] 没有对应的开始标签")));
+            }
+
+            let Some(block) = tag.strip_prefix('#') else {
+                let (spec, fallback) = match tag.split_once('?') {
+                    Some((spec, fallback)) => (spec, Some(fallback.to_string())),
+                    None => (tag, None),
+                };
+                let mut parts = spec.split('|');
+                let name = parts.next().unwrap_or_default();
+                let field = lookup_field(name, package)?;
+                let filters: Vec<&str> = parts.collect();
+                let filters = Filters::parse(field, field.kind(package), &filters)?;
+                segments.push(Segment::Field {
+                    field,
+                    filters,
+                    fallback,
+                });
+                continue;
+            };
+
+            let (keyword, argument) = match block.split_once(' ') {
+                Some((keyword, argument)) => (keyword, Some(argument.trim())),
+                None => (block, None),
+            };
+
+            if keyword == "否则" || keyword == "else" {
+                if allow_else {
+                    return Ok((segments, true));
+                }
+                return Err(Error::Format("[#否则] 只能用在条件块中".to_string()));
+            }
+
+            if let Some(condition) = Condition::lookup(keyword) {
+                let name = argument
+                    .ok_or_else(|| Error::Format(format!("[#{keyword}] 缺少占位符名称")))?;
+                let field = lookup_field(name, package)?;
+                let (then, has_else) = self.parse_until(Some(keyword), package, true)?;
+                let otherwise = if has_else {
+                    self.parse_block(Some(keyword), package)?.0
+                } else {
+                    Vec::new()
+                };
+                segments.push(Segment::If {
+                    condition,
+                    field,
+                    then,
+                    otherwise,
+                });
+                continue;
+            }
+
+            if let Some(resource) = RESOURCES.into_iter().find(|resource| {
+                resource.package_block() == keyword || resource.package_block_alias() == keyword
+            }) {
+                if argument.is_some() {
+                    return Err(Error::Format(format!("[#{keyword}] 不需要参数")));
+                }
+                if package.is_some() {
+                    return Err(Error::Format(format!("[#{keyword}] 不能嵌套在循环中")));
+                }
+                let (body, _) = self.parse_block(Some(keyword), Some(resource))?;
+                segments.push(Segment::Each { resource, body });
+                continue;
+            }
+
+            return Err(Error::Format(format!("未知的块 [#{keyword}]")));
+        }
+
+        match closing {
+            Some(closing) => Err(Error::Format(format!("缺少 [/{closing}]"))),
+            None => Ok((segments, false)),
+        }
+    }
+}
+
+// {..} 中的内容是否为已知的占位符、块或结束标签
+fn is_known_tag(tag: &str) -> bool {
+    let is_block = |keyword: &str| {
+        Condition::lookup(keyword).is_some()
+            || RESOURCES.into_iter().any(|resource| {
+                resource.package_block() == keyword || resource.package_block_alias() == keyword
+            })
+    };
+    if let Some(name) = tag.strip_prefix('/') {
+        return is_block(name);
+    }
+    if let Some(block) = tag.strip_prefix('#') {
+        let keyword = block.split(' ').next().unwrap_or_default();
+        return matches!(keyword, "否则" | "else") || is_block(keyword);
+    }
+    let spec = tag.split('?').next().unwrap_or_default();
+    let name = spec.split('|').next().unwrap_or_default();
+    Field::lookup(name).is_some()
+}
+
+fn lookup_field(name: &str, package: Option<Resource>) -> Result<Field> {
+    let field =
+        Field::lookup(name).ok_or_else(|| Error::Format(format!("未知的占位符 [{name}]")))?;
+    if field.is_package() && package.is_none() {
+        return Err(Error::Format(format!(
+            "[{name}] 只能用在 [#流量包]、[#通话包]、[#短信包] 循环中"
+        )));
+    }
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    use super::*;
+    use crate::{DEFAULT_FORMAT, DEFAULT_FORMAT_WITH_LAST};
+
+    fn gb(gb: f64) -> DataAmount {
+        DataAmount::from_gb(gb)
+    }
+
+    // 定向 5GB 已用 1GB, 通用 10GB 已用 2.5GB, 通话 100 分钟已用 30 分钟, 短信 50 条已用 5 条
+    fn data() -> ChinaUnicomData {
+        ChinaUnicomData {
+            package_name: "测试套餐".to_string(),
+            time: Local.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap(),
+            sum_flow_used: gb(3.5),
+            limit_flow_used: gb(1.0),
+            non_limit_flow_used: gb(2.5),
+            free_flow_used: gb(0.5),
+            non_free_flow_used: gb(3.0),
+            sum_flow: Quota::Limited(gb(15.0)),
+            limit_flow: Quota::Limited(gb(5.0)),
+            non_limit_flow: Quota::Limited(gb(10.0)),
+            sum_voice_used: 30,
+            non_limit_voice_used: 30,
+            sum_voice: Quota::Limited(100),
+            non_limit_voice: Quota::Limited(100),
+            sum_sms_used: 5,
+            non_limit_sms_used: 5,
+            sum_sms: Quota::Limited(50),
+            non_limit_sms: Quota::Limited(50),
+            high_speed_flow_remaining: Some(gb(20.0)),
+            carry_over_flow: gb(1.0),
+            carry_over_flow_used: DataAmount::from_mb(256.0),
+            carry_over_flow_expiry: NaiveDate::from_ymd_opt(2026, 10, 31),
+            flow_packages: vec![
+                PackageUsage {
+                    fee_policy_name: "通用流量包".to_string(),
+                    limited: false,
+                    total: Quota::Limited(gb(10.0)),
+                    used: gb(2.5),
+                    remaining: Quota::Limited(gb(7.5)),
+                    end_date: NaiveDate::from_ymd_opt(2026, 10, 31),
+                    ..Default::default()
+                },
+                PackageUsage {
+                    fee_policy_name: "定向流量包".to_string(),
+                    limited: true,
+                    total: Quota::Limited(gb(5.0)),
+                    used: gb(1.0),
+                    remaining: Quota::Limited(gb(4.0)),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    // 3 时 30 分之前的查询: 通用流量少用 512MB, 通话少用 10 分钟
+    fn last() -> ChinaUnicomData {
+        let mut last = data();
+        last.time = Local.with_ymd_and_hms(2026, 10, 18, 8, 30, 0).unwrap();
+        last.sum_flow_used = gb(3.0);
+        last.non_limit_flow_used = gb(2.0);
+        last.non_free_flow_used = gb(2.5);
+        last.sum_voice_used = 20;
+        last.non_limit_voice_used = 20;
+        last.flow_packages[0].used = gb(2.0);
+        last
+    }
+
+    fn render(source: &str) -> String {
+        Template::compile(source).unwrap().render(&data()).unwrap()
+    }
+
+    fn render_with_last(source: &str) -> String {
+        Template::compile(source)
+            .unwrap()
+            .render_with_last(&data(), &last())
+            .unwrap()
+    }
+
+    fn compile_error(source: &str) -> String {
+        match Template::compile(source) {
+            Err(Error::Format(message)) => message,
+            other => panic!("{source:?} 应当编译失败, 实际为 {other:?}"),
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        assert_eq!(
+            render("[套餐名称]: [流量通用余量], [通话总用量]"),
+            "测试套餐: 7.50GB, 30分钟"
+        );
+        assert_eq!(render("[时间]"), "2026-10-18 12:00:00");
+    }
+
+    #[test]
+    fn doubled_bracket_is_literal() {
+        assert_eq!(render("[[流量总量] [流量总量]"), "[流量总量] 15.00GB");
+    }
+
+    #[test]
+    fn unknown_placeholder_is_error() {
+        assert!(compile_error("[流量总余额]").contains("流量总余额"));
+    }
+
+    #[test]
+    fn unclosed_placeholder_is_error() {
+        assert!(compile_error("余量 [流量总余量").contains("缺少 ]"));
+    }
+
+    #[test]
+    fn interval_placeholder_needs_last() {
+        let template = Template::compile("[区间流量总用量]").unwrap();
+        assert!(template.needs_delta());
+        assert!(matches!(template.render(&data()), Err(Error::Format(_))));
+        assert_eq!(
+            template.render_with_last(&data(), &last()).unwrap(),
+            "512.00MB"
+        );
+        assert!(!Template::compile("[流量总量]").unwrap().needs_delta());
+    }
+
+    #[test]
+    fn cycle_decides_reset_in_render_with_last() {
+        // 上次查询在 15 日之前且用量更高, 按 15 日重置的账期视为重置
+        let mut last = last();
+        last.time = Local.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap();
+        last.sum_flow_used = gb(8.0);
+        let template = Template::compile("[区间流量总用量]")
+            .unwrap()
+            .cycle(BillingCycle::new(15));
+
+        assert_eq!(template.render_with_last(&data(), &last).unwrap(), "3.50GB");
+    }
+
+    #[test]
+    fn default_format() {
+        assert_eq!(
+            render(DEFAULT_FORMAT),
+            "时间: 2026-10-18 12:00:00
+流量:
+    * 总量:
+        - 总流量: 15.00GB
+        - 定向流量: 5.00GB
+        - 通用流量: 10.00GB
+    * 用量:
+        - 总流量: 3.50GB
+        - 定向流量: 1.00GB
+        - 通用流量: 2.50GB
+        - 免费流量: 512.00MB
+        - 收费流量: 3.00GB
+    * 余量:
+        - 总流量: 11.50GB
+        - 定向流量: 4.00GB
+        - 通用流量: 7.50GB
+    * 结转:
+        - 剩余: 768.00MB
+        - 到期: 2026-10-31
+    * 限速:
+        - 状态: 未限速
+        - 高速流量余量: 20.00GB
+通话:
+    * 总量:
+        - 总通话时长: 100分钟
+        - 定向通话时长: 0分钟
+        - 通用通话时长: 100分钟
+    * 用量:
+        - 总通话时长: 30分钟
+        - 定向通话时长: 0分钟
+        - 通用通话时长: 30分钟
+    * 余量:
+        - 总通话时长: 70分钟
+        - 定向通话时长: 0分钟
+        - 通用通话时长: 70分钟
+短信:
+    * 总量:
+        - 总短信: 50条
+        - 定向短信: 0条
+        - 通用短信: 50条
+    * 用量:
+        - 总短信: 5条
+        - 定向短信: 0条
+        - 通用短信: 5条
+    * 余量:
+        - 总短信: 45条
+        - 定向短信: 0条
+        - 通用短信: 45条"
+        );
+    }
+
+    #[test]
+    fn default_format_hides_empty_sections() {
+        let data = ChinaUnicomData {
+            time: Local.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap(),
+            sum_flow: Quota::Limited(gb(10.0)),
+            non_limit_flow: Quota::Limited(gb(10.0)),
+            ..Default::default()
+        };
+        let output = Template::compile(DEFAULT_FORMAT)
+            .unwrap()
+            .render(&data)
+            .unwrap();
+        assert!(!output.contains("定向流量"));
+        assert!(!output.contains("结转"));
+        assert!(!output.contains("通话"));
+        assert!(!output.contains("短信"));
+        assert!(!output.contains("超出"));
+        assert!(output.ends_with("- 高速流量余量: 无"));
+    }
+
+    #[test]
+    fn default_format_with_last() {
+        let output = render_with_last(DEFAULT_FORMAT_WITH_LAST);
+        assert!(output.starts_with(
+            "区间信息:
+    * 时长: 3小时30分钟
+    * 流量:
+        - 总流量: 512.00MB
+        - 免费流量: 0KB
+        - 收费流量: 512.00MB
+        - 通用流量: 512.00MB
+        - 定向流量: 0KB
+    * 通话:
+        - 总通话时长: 10分钟
+        - 定向通话时长: 0分钟
+        - 通用通话时长: 10分钟
+    * 短信:
+        - 总短信: 0条
+        - 定向短信: 0条
+        - 通用短信: 0条
+查询信息:
+    时间: 2026-10-18 12:00:00
+    流量:
+        * 总量:
+            - 总流量: 15.00GB"
+        ));
+        assert!(output.ends_with(
+            "        * 余量:
+            - 总短信: 45条
+            - 定向短信: 0条
+            - 通用短信: 45条"
+        ));
+    }
+
+    #[test]
+    fn fallback_for_zero_or_missing() {
+        assert_eq!(render("[通话定向总量?无定向通话]"), "无定向通话");
+        assert_eq!(render("[通话通用总量?无通用通话]"), "100分钟");
+        let data = ChinaUnicomData::default();
+        let output = Template::compile("[结转流量到期?无结转]")
+            .unwrap()
+            .render(&data)
+            .unwrap();
+        assert_eq!(output, "无结转");
+    }
+
+    #[test]
+    fn conditional_blocks() {
+        assert_eq!(render("[#非零 流量定向总量]有定向[/非零]"), "有定向");
+        assert_eq!(render("[#非零 通话定向总量]有定向[/非零]"), "");
+        assert_eq!(
+            render("[#非零 通话定向总量]有[#否则]没有[/非零]定向通话"),
+            "没有定向通话"
+        );
+        assert_eq!(render("[#有限 流量通用总量]有限[/有限]"), "有限");
+        assert_eq!(render("[#无限 流量通用总量]无限[/无限]"), "");
+
+        let mut data = data();
+        data.non_limit_flow = Quota::Unlimited;
+        data.sum_flow = Quota::Unlimited;
+        let output = Template::compile("[#无限 流量通用总量]不限量[#否则][流量通用余量][/无限]")
+            .unwrap()
+            .render(&data)
+            .unwrap();
+        assert_eq!(output, "不限量");
+    }
+
+    #[test]
+    fn package_loop() {
+        assert_eq!(
+            render("[#流量包][包名称]([包类型]): [包余量]/[包总量] 到期 [包到期?无]\n[/流量包]"),
+            "通用流量包(通用): 7.50GB/10.00GB 到期 2026-10-31\n\
+             定向流量包(定向): 4.00GB/5.00GB 到期 无\n"
+        );
+        assert_eq!(render("[#通话包][包名称][/通话包]"), "");
+        assert_eq!(
+            render_with_last("[#流量包][包名称] [区间包用量]; [/流量包]"),
+            "通用流量包 512.00MB; 定向流量包 0KB; "
+        );
+    }
+
+    #[test]
+    fn nested_blocks() {
+        assert_eq!(
+            render(
+                "[#流量包][#非零 包用量][#有限 包总量][包名称] 已用 [包用量][/有限][#否则]未使用[/非零];[/流量包]"
+            ),
+            "通用流量包 已用 2.50GB;定向流量包 已用 1.00GB;"
+        );
+        assert_eq!(
+            render("[#非零 流量总量][#非零 通话定向总量]A[#否则]B[/非零]C[#否则]D[/非零]"),
+            "BC"
+        );
+    }
+
+    #[test]
+    fn block_errors() {
+        assert!(compile_error("[#非零 流量总量]未结束").contains("缺少 [/非零]"));
+        assert!(compile_error("[/非零]").contains("没有对应的开始标签"));
+        assert!(compile_error("[#非零 流量总量]A[/有限]").contains("[/有限]"));
+        assert!(compile_error("[#否则]").contains("只能用在条件块中"));
+        assert!(compile_error("[#非零]A[/非零]").contains("缺少占位符名称"));
+        assert!(compile_error("[#重复]A[/重复]").contains("未知的块"));
+        assert!(compile_error("[#流量包 流量总量][/流量包]").contains("不需要参数"));
+        assert!(compile_error("[#流量包][#通话包][/通话包][/流量包]").contains("不能嵌套"));
+        assert!(compile_error("[包名称]").contains("只能用在"));
+    }
+
+    #[test]
+    fn unlimited_package_shows_unlimited() {
+        let mut data = data();
+        data.flow_packages[0].total = Quota::Unlimited;
+        data.flow_packages[0].remaining = Quota::Unlimited;
+        let output = Template::compile("[#流量包][包余量]/[包总量] [/流量包]")
+            .unwrap()
+            .render(&data)
+            .unwrap();
+        assert_eq!(output, "无限/无限 4.00GB/5.00GB ");
+    }
+
+    #[test]
+    fn unit_and_precision_filters() {
+        assert_eq!(render("[流量通用余量|MB]"), "7680.00MB");
+        assert_eq!(render("[流量通用余量|mb|0]"), "7680MB");
+        assert_eq!(render("[流量通用余量|GB|1]"), "7.5GB");
+        assert_eq!(render("[流量免费用量|KB]"), "524288KB");
+        assert_eq!(render("[流量免费用量|auto|1]"), "512.0MB");
+        assert_eq!(render("[通话总用量|小时]"), "0.50小时");
+        assert_eq!(render("[通话总用量|小时|1]"), "0.5小时");
+        assert_eq!(render_with_last("[区间时长|分钟]"), "210分钟");
+        assert_eq!(render_with_last("[区间时长|小时|1]"), "3.5小时");
+    }
+
+    #[test]
+    fn percent_and_raw_filters() {
+        assert_eq!(render("[流量通用用量|%]"), "25%");
+        assert_eq!(render("[流量通用用量|%|1]"), "25.0%");
+        assert_eq!(render("[流量免费用量|%|2]"), "3.33%");
+        assert_eq!(render("[流量通用余量|raw]"), "7864320");
+        assert_eq!(render("[流量通用余量|GB|raw]"), "7.50");
+        assert_eq!(render("[通话总余量|raw]"), "70");
+        assert_eq!(render("[流量通用用量|%|raw]"), "25");
+        assert_eq!(render_with_last("[区间时长|raw]"), "210");
+        assert_eq!(render("[通话定向用量|%]"), "无");
+    }
+
+    #[test]
+    fn invalid_filters_are_errors() {
+        assert!(compile_error("[时间|MB]").contains("不支持过滤器"));
+        assert!(compile_error("[套餐名称|raw]").contains("不支持过滤器"));
+        assert!(compile_error("[通话总用量|MB]").contains("不支持过滤器 |MB"));
+        assert!(compile_error("[流量总用量|小时]").contains("不支持过滤器 |小时"));
+        assert!(compile_error("[短信总用量|分钟]").contains("不支持过滤器 |分钟"));
+        assert!(compile_error("[流量超出|%]").contains("不支持过滤器 |%"));
+        assert!(compile_error("[流量通用用量|%|MB]").contains("不能与单位同时使用"));
+        assert!(compile_error("[流量通用用量|PB]").contains("未知的过滤器 |PB"));
+    }
+
+    #[test]
+    fn english_aliases() {
+        assert_eq!(
+            render("{flow.general.remaining|GB|1} {voice.sum.used|h|1}"),
+            "7.5GB 0.5小时"
+        );
+        assert_eq!(
+            render("{#nonzero voice.limited.total}A{#else}B{/nonzero} [流量通用余量]"),
+            "B 7.50GB"
+        );
+        assert_eq!(
+            render("{#flow.packages}{package.name};{/flow.packages}"),
+            "通用流量包;定向流量包;"
+        );
+        assert_eq!(render_with_last("{interval.flow.sum.used}"), "512.00MB");
+    }
+
+    #[test]
+    fn locale() {
+        let template = Template::compile(
+            "{voice.sum.used}, {sms.sum.remaining}, {throttle.state}, {voice.limited.used|%}",
+        )
+        .unwrap()
+        .locale(Locale::En);
+        assert_eq!(
+            template.render(&data()).unwrap(),
+            "30 min, 45 SMS, not throttled, N/A"
+        );
+        let output = Template::compile("[区间时长]")
+            .unwrap()
+            .locale(Locale::En)
+            .render_with_last(&data(), &last())
+            .unwrap();
+        assert_eq!(output, "3h 30m");
+    }
+
+    #[test]
+    fn unknown_braces_are_literal() {
+        assert_eq!(render(r#"{"a":1}"#), r#"{"a":1}"#);
+        assert_eq!(
+            render("余量 {注意} {flow.sum.total}"),
+            "余量 {注意} 15.00GB"
+        );
+        assert_eq!(
+            render(r#"{"total":"{flow.sum.total}"}"#),
+            r#"{"total":"15.00GB"}"#
+        );
+        assert_eq!(render("{ 未闭合"), "{ 未闭合");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            render(r"\[流量总量\] \{flow.sum.total\}"),
+            "[流量总量] {flow.sum.total}"
+        );
+        assert_eq!(render("{{flow.sum.total}"), "{flow.sum.total}");
+        assert_eq!(render(r"C:\path\n"), r"C:\path\n");
+    }
+
+    #[test]
+    fn percentage_placeholders() {
+        assert_eq!(render("[流量通用用量百分比]"), "25%");
+        assert_eq!(render("[流量通用余量百分比]"), "75%");
+        assert_eq!(render("[流量总用量百分比|1]"), "23.3%");
+        assert_eq!(render("[通话通用用量百分比|raw]"), "30");
+        assert_eq!(render("[通话定向用量百分比]"), "无");
+        assert_eq!(render("{flow.general.remaining_percent}"), "75%");
+    }
+
+    #[test]
+    fn progress_bar_placeholders() {
+        assert_eq!(render("[流量通用进度条]"), "▓▓▓░░░░░░░ 25%");
+        assert_eq!(render("[短信进度条]"), "▓░░░░░░░░░ 10%");
+        assert_eq!(render("[通话定向进度条]"), "无");
+        assert_eq!(render("{flow.limited.bar}"), "▓▓░░░░░░░░ 20%");
+
+        let output = Template::compile("[流量通用进度条]")
+            .unwrap()
+            .progress_bar(ProgressBar {
+                width: 4,
+                filled: '#',
+                empty: '.',
+                show_percent: false,
+            })
+            .render(&data())
+            .unwrap();
+        assert_eq!(output, "#...");
+    }
+
+    #[test]
+    fn progress_bar_overflow_and_unlimited() {
+        assert_eq!(ProgressBar::default().render(150.0), "▓▓▓▓▓▓▓▓▓▓ 150%");
+        assert_eq!(ProgressBar::default().render(-5.0), "░░░░░░░░░░ -5%");
+
+        let mut data = data();
+        data.non_limit_flow = Quota::Unlimited;
+        let output = Template::compile("[流量通用进度条] [流量通用用量百分比]")
+            .unwrap()
+            .render(&data)
+            .unwrap();
+        assert_eq!(output, "无限 无");
+    }
+
+    #[test]
+    fn progress_bar_rejects_filters() {
+        assert!(compile_error("[流量进度条|MB]").contains("不支持过滤器"));
+        assert!(compile_error("[流量进度条|%]").contains("不支持过滤器"));
+    }
+}
@@ -0,0 +1,212 @@
+// 集成测试共用的本地 HTTP 服务器, 每个连接只处理一个请求
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use china_unicom_rs::ChinaUnicomClient;
+use reqwest::Url;
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+// 服务器收到的请求
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // 解析 application/x-www-form-urlencoded 请求体
+    pub fn form(&self) -> HashMap<String, String> {
+        let url = Url::parse(&format!("http://localhost/?{}", self.body)).unwrap();
+        url.query_pairs().into_owned().collect()
+    }
+}
+
+// 服务器返回的响应
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Reply {
+    pub fn json(body: Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn html(body: &str) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "text/html".to_string())],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // 下发 ecs_token 与 ecs_acc
+    pub fn ecs_cookies(self, token: &str, acc: &str) -> Self {
+        self.header("Set-Cookie", &format!("ecs_token={token}; Path=/"))
+            .header("Set-Cookie", &format!("ecs_acc={acc}; Path=/"))
+    }
+}
+
+type Handler = dyn Fn(&Request) -> Reply + Send + Sync;
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    pub async fn start(handler: impl Fn(&Request) -> Reply + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, handler, recorded).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn client(&self) -> ChinaUnicomClient {
+        ChinaUnicomClient::builder()
+            .base_url(&self.url)
+            .build()
+            .unwrap()
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // 请求了 path 的次数
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|request| request.path == path)
+            .count()
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    handler: Arc<Handler>,
+    requests: Arc<Mutex<Vec<Request>>>,
+) -> std::io::Result<()> {
+    let (read, mut write) = stream.split();
+    let mut reader = BufReader::new(read);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.trim_end().split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    let request = Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    let reply = handler(&request);
+    requests.lock().unwrap().push(request);
+
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+    response.push_str("\r\n");
+    response.push_str(&reply.body);
+    write.write_all(response.as_bytes()).await?;
+    write.shutdown().await
+}
+
+// 一个可以正常解析的余量查询响应: 通用流量 10GB 已用 1GB, 通话 100 分钟已用 10 分钟
+pub fn query_body() -> Value {
+    serde_json::json!({
+        "code": "0000",
+        "sumresource": 1024,
+        "sum": "0",
+        "packageName": "测试套餐",
+        "summary": { "freeFlow": "0" },
+        "resources": [
+            {
+                "type": "flow",
+                "details": [
+                    { "feePolicyName": "通用流量", "limited": "0", "total": "10240", "use": "1024", "remain": "9216" }
+                ]
+            },
+            {
+                "type": "Voice",
+                "details": [
+                    { "feePolicyName": "通话", "limited": "0", "total": "100", "use": "10", "remain": "90" }
+                ]
+            }
+        ]
+    })
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use china_unicom_rs::{login::PasswordLoginOutcome, Error};
+use common::{MockServer, Reply};
+use serde_json::json;
+
+const SEND_SMS_CODE_PATH: &str = "/mobileService/sendRadomNum.htm";
+const SMS_LOGIN_PATH: &str = "/mobileService/radomLogin.htm";
+const PASSWORD_LOGIN_PATH: &str = "/mobileService/login.htm";
+
+#[tokio::test]
+async fn send_sms_code_posts_mobile() {
+    let server = MockServer::start(|_| Reply::json(json!({ "code": "0" }))).await;
+
+    server
+        .client()
+        .send_sms_code("18600000000", "app")
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, SEND_SMS_CODE_PATH);
+    let form = requests[0].form();
+    assert_eq!(form["mobile"], "18600000000");
+    assert_eq!(form["appId"], "app");
+}
+
+#[tokio::test]
+async fn send_sms_code_rejected() {
+    let server =
+        MockServer::start(|_| Reply::json(json!({ "code": "1", "desc": "发送过于频繁" }))).await;
+
+    let err = server
+        .client()
+        .send_sms_code("18600000000", "app")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::LoginFailed { code, desc } if code == "1" && desc == "发送过于频繁"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn sms_login_returns_token_and_cookies() {
+    let server = MockServer::start(|_| {
+        Reply::json(json!({ "code": "0", "token_online": "token" })).ecs_cookies("t1", "a1")
+    })
+    .await;
+
+    let resp = server
+        .client()
+        .sms_login("18600000000", "123456", "app")
+        .await
+        .unwrap();
+
+    assert_eq!(resp.online_token, "token");
+    assert_eq!(resp.cookie, "ecs_token=t1; ecs_acc=a1");
+    let requests = server.requests();
+    assert_eq!(requests[0].path, SMS_LOGIN_PATH);
+    assert_eq!(requests[0].form()["password"], "123456");
+}
+
+#[tokio::test]
+async fn sms_login_wrong_code_fails() {
+    let server =
+        MockServer::start(|_| Reply::json(json!({ "code": "1", "desc": "短信验证码错误" }))).await;
+
+    let err = server
+        .client()
+        .sms_login("18600000000", "000000", "app")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::LoginFailed { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn sms_login_without_cookies_fails() {
+    let server =
+        MockServer::start(|_| Reply::json(json!({ "code": "0", "token_online": "token" }))).await;
+
+    let err = server
+        .client()
+        .sms_login("18600000000", "123456", "app")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::MissingCookie(_)), "{err:?}");
+}
+
+async fn password_login(
+    reply: Reply,
+) -> (MockServer, china_unicom_rs::Result<PasswordLoginOutcome>) {
+    let server = MockServer::start(move |_| reply.clone()).await;
+    let outcome = server
+        .client()
+        .password_login("18600000000", "password", "app")
+        .await;
+    (server, outcome)
+}
+
+#[tokio::test]
+async fn password_login_success_encrypts_credentials() {
+    let (server, outcome) = password_login(
+        Reply::json(json!({ "code": "0", "token_online": "token" })).ecs_cookies("t1", "a1"),
+    )
+    .await;
+
+    let PasswordLoginOutcome::Success(resp) = outcome.unwrap() else {
+        panic!("登录应当成功");
+    };
+    assert_eq!(resp.online_token, "token");
+    assert_eq!(resp.cookie, "ecs_token=t1; ecs_acc=a1");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, PASSWORD_LOGIN_PATH);
+    let form = requests[0].form();
+    assert_ne!(form["mobile"], "18600000000");
+    assert_ne!(form["password"], "password");
+}
+
+#[tokio::test]
+async fn password_login_captcha_by_code() {
+    let (_server, outcome) =
+        password_login(Reply::json(json!({ "code": "4", "desc": "请完成验证" }))).await;
+
+    assert!(
+        matches!(outcome, Ok(PasswordLoginOutcome::CaptchaRequired { ref code, .. }) if code == "4"),
+        "{outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn password_login_second_factor_by_code() {
+    let (_server, outcome) =
+        password_login(Reply::json(json!({ "code": "8", "desc": "请完成验证" }))).await;
+
+    assert!(
+        matches!(outcome, Ok(PasswordLoginOutcome::SecondFactorRequired { ref code, .. }) if code == "8"),
+        "{outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn password_login_captcha_by_desc() {
+    let (_server, outcome) = password_login(Reply::json(
+        json!({ "code": "99", "desc": "请输入图形验证码" }),
+    ))
+    .await;
+
+    assert!(
+        matches!(outcome, Ok(PasswordLoginOutcome::CaptchaRequired { .. })),
+        "{outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn password_login_second_factor_by_desc() {
+    let (_server, outcome) = password_login(Reply::json(
+        json!({ "code": "99", "desc": "非常用设备登录, 请进行二次验证" }),
+    ))
+    .await;
+
+    assert!(
+        matches!(
+            outcome,
+            Ok(PasswordLoginOutcome::SecondFactorRequired { .. })
+        ),
+        "{outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn password_login_wrong_sms_code_is_not_second_factor() {
+    let (_server, outcome) = password_login(Reply::json(
+        json!({ "code": "1", "desc": "短信验证码错误" }),
+    ))
+    .await;
+
+    assert!(
+        matches!(outcome, Err(Error::LoginFailed { .. })),
+        "{outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn password_login_wrong_password_fails() {
+    let (_server, outcome) =
+        password_login(Reply::json(json!({ "code": "2", "desc": "密码错误" }))).await;
+
+    assert!(
+        matches!(outcome, Err(Error::LoginFailed { ref code, .. }) if code == "2"),
+        "{outcome:?}"
+    );
+}
+
+#[tokio::test]
+async fn password_login_server_error() {
+    let (_server, outcome) = password_login(Reply::status(502, "Bad Gateway")).await;
+
+    assert!(
+        matches!(outcome, Err(Error::Http { status: 502, .. })),
+        "{outcome:?}"
+    );
+}
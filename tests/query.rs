@@ -0,0 +1,311 @@
+mod common;
+
+use china_unicom_rs::{DataAmount, Error, Quota};
+use chrono::NaiveDate;
+use common::{query_body, MockServer, Reply};
+use serde_json::json;
+
+const QUERY_PATH: &str =
+    "/servicequerybusiness/operationservice/queryOcsPackageFlowLeftContentRevisedInJune";
+const ONLINE_PATH: &str = "/mobileService/onLine.htm";
+
+async fn query(reply: Reply) -> china_unicom_rs::Result<china_unicom_rs::data::ChinaUnicomData> {
+    let server = MockServer::start(move |_| reply.clone()).await;
+    server
+        .client()
+        .query_china_unicom_data("ecs_token=t; ecs_acc=a")
+        .await
+}
+
+#[tokio::test]
+async fn query_parses_usage_and_sends_cookie() {
+    let server = MockServer::start(|_| Reply::json(query_body())).await;
+
+    let data = server
+        .client()
+        .query_china_unicom_data("ecs_token=t; ecs_acc=a")
+        .await
+        .unwrap();
+
+    assert_eq!(data.package_name, "测试套餐");
+    assert_eq!(data.sum_flow, Quota::Limited(DataAmount::from_gb(10.0)));
+    assert_eq!(data.sum_flow_used, DataAmount::from_gb(1.0));
+    assert_eq!(data.sum_voice, Quota::Limited(100));
+    assert_eq!(data.sum_voice_used, 10);
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, QUERY_PATH);
+    assert_eq!(requests[0].header("cookie"), Some("ecs_token=t; ecs_acc=a"));
+}
+
+#[tokio::test]
+async fn packages_are_listed_per_resource() {
+    let mut body = query_body();
+    body["resources"][0]["details"][0]["endDate"] = json!("2024-09-30");
+
+    let data = query(Reply::json(body)).await.unwrap();
+
+    let [package] = data.flow_packages.as_slice() else {
+        panic!("{:?}", data.flow_packages);
+    };
+    assert_eq!(package.fee_policy_name, "通用流量");
+    assert!(!package.limited);
+    assert_eq!(package.total, Quota::Limited(DataAmount::from_gb(10.0)));
+    assert_eq!(package.used, DataAmount::from_gb(1.0));
+    assert_eq!(package.remaining, Quota::Limited(DataAmount::from_gb(9.0)));
+    assert_eq!(package.end_date, NaiveDate::from_ymd_opt(2024, 9, 30));
+    assert_eq!(data.voice_packages.len(), 1);
+    assert_eq!(data.voice_packages[0].remaining, Quota::Limited(90));
+    assert!(data.sms_packages.is_empty());
+}
+
+#[tokio::test]
+async fn sms_falls_back_to_summary_fields() {
+    let mut body = query_body();
+    body["smsSumresource"] = json!(5);
+    body["canUseSmsAll"] = json!("95");
+
+    let data = query(Reply::json(body)).await.unwrap();
+
+    assert_eq!(data.sum_sms, Quota::Limited(100));
+    assert_eq!(data.sum_sms_used, 5);
+    assert_eq!(data.non_limit_sms, Quota::Limited(100));
+}
+
+#[tokio::test]
+async fn unparseable_sms_summary_is_error() {
+    let mut body = query_body();
+    body["canUseSmsAll"] = json!("不限");
+
+    let err = query(Reply::json(body)).await.unwrap_err();
+    assert!(
+        matches!(&err, Error::Parse { field, .. } if field == "canUseSmsAll"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn server_error_is_http_error() {
+    let err = query(Reply::status(503, "Service Unavailable"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Http { status: 503, .. }), "{err:?}");
+    assert!(!err.is_session_expired());
+}
+
+#[tokio::test]
+async fn unauthorized_is_session_expired() {
+    let err = query(Reply::status(401, "")).await.unwrap_err();
+    assert!(err.is_session_expired(), "{err:?}");
+}
+
+#[tokio::test]
+async fn login_page_is_session_expired() {
+    let err = query(Reply::html(
+        "<html><head><title>登录</title></head><body>请登录</body></html>",
+    ))
+    .await
+    .unwrap_err();
+    assert!(err.is_session_expired(), "{err:?}");
+}
+
+#[tokio::test]
+async fn other_html_is_unexpected() {
+    let err = query(Reply::html("<html><body>系统维护中</body></html>"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::UnexpectedResponse { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn expiry_desc_is_session_expired() {
+    let err = query(Reply::json(
+        json!({ "code": "999999", "desc": "登录已失效, 请重新登录" }),
+    ))
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(&err, Error::SessionExpired { code, .. } if code == "999999"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn unrelated_token_desc_is_unexpected() {
+    let err = query(Reply::json(
+        json!({ "code": "500", "desc": "token 参数格式错误" }),
+    ))
+    .await
+    .unwrap_err();
+    assert!(matches!(err, Error::UnexpectedResponse { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn other_error_code_is_unexpected() {
+    let err = query(Reply::json(json!({ "code": "500", "desc": "系统繁忙" })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::UnexpectedResponse { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn unparseable_field_is_error() {
+    let mut body = query_body();
+    body["resources"][0]["details"][0]["total"] = json!("abc");
+    let err = query(Reply::json(body)).await.unwrap_err();
+    assert!(matches!(err, Error::Parse { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn sparse_detail_counts_as_zero() {
+    let mut body = query_body();
+    body["resources"][0]["details"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "feePolicyName": "空资源包", "limited": "0", "total": null }));
+    body["resources"][1]["details"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "feePolicyName": "空通话包", "limited": "0" }));
+
+    let data = query(Reply::json(body)).await.unwrap();
+
+    assert_eq!(data.sum_flow, Quota::Limited(DataAmount::from_gb(10.0)));
+    assert_eq!(data.sum_voice, Quota::Limited(100));
+    assert_eq!(data.sum_voice_used, 10);
+}
+
+// 在测试数据的流量资源中追加明细
+fn with_flow_details(details: &[serde_json::Value]) -> serde_json::Value {
+    let mut body = query_body();
+    body["resources"][0]["details"]
+        .as_array_mut()
+        .unwrap()
+        .extend_from_slice(details);
+    body
+}
+
+#[tokio::test]
+async fn carry_over_is_counted_separately() {
+    let body = with_flow_details(&[json!({
+        "feePolicyName": "上月结转流量",
+        "limited": "0",
+        "total": "2048",
+        "use": "512",
+        "remain": "1536",
+        "endDate": "2024-09-30"
+    })]);
+
+    let data = query(Reply::json(body)).await.unwrap();
+
+    assert_eq!(data.sum_flow, Quota::Limited(DataAmount::from_gb(10.0)));
+    assert_eq!(data.sum_flow_used, DataAmount::from_gb(1.0));
+    assert_eq!(data.carry_over_flow, DataAmount::from_gb(2.0));
+    assert_eq!(data.carry_over_flow_used, DataAmount::from_mb(512.0));
+    assert_eq!(
+        data.carry_over_flow_remaining(),
+        DataAmount::from_mb(1536.0)
+    );
+    let carry_over: Vec<_> = data.flow_packages.iter().map(|p| p.carry_over).collect();
+    assert_eq!(carry_over, [false, true]);
+}
+
+#[tokio::test]
+async fn look_alike_name_is_not_carry_over() {
+    let body = with_flow_details(&[
+        // 名称相似但不含 "结转", beforeTotal 为 0
+        json!({
+            "feePolicyName": "转结流量包",
+            "limited": "0",
+            "total": "1024",
+            "use": "0",
+            "beforeTotal": "0"
+        }),
+        // 有 beforeTotal 但接口要求隐藏结转标签
+        json!({
+            "feePolicyName": "流量加油包",
+            "limited": "0",
+            "total": "1024",
+            "use": "0",
+            "beforeTotal": "1024",
+            "hideCarryForwardLabel": "1"
+        }),
+    ]);
+
+    let data = query(Reply::json(body)).await.unwrap();
+
+    assert_eq!(data.sum_flow, Quota::Limited(DataAmount::from_gb(12.0)));
+    assert_eq!(data.carry_over_flow, DataAmount::ZERO);
+    assert_eq!(data.carry_over_flow_expiry, None);
+    assert!(data.flow_packages.iter().all(|p| !p.carry_over));
+}
+
+#[tokio::test]
+async fn carry_over_expiry_is_earliest_end_date() {
+    let body = with_flow_details(&[
+        json!({
+            "feePolicyName": "结转流量",
+            "limited": "0",
+            "total": "1024",
+            "use": "0",
+            "endDate": "2024-10-31"
+        }),
+        // total 为空时取 beforeTotal
+        json!({
+            "feePolicyName": "国内流量",
+            "limited": "0",
+            "total": "",
+            "use": "",
+            "beforeTotal": "1024",
+            "beforeUse": "256",
+            "endDate": "2024-09-30"
+        }),
+    ]);
+
+    let data = query(Reply::json(body)).await.unwrap();
+
+    assert_eq!(data.carry_over_flow, DataAmount::from_gb(2.0));
+    assert_eq!(data.carry_over_flow_used, DataAmount::from_mb(256.0));
+    assert_eq!(
+        data.carry_over_flow_expiry,
+        NaiveDate::from_ymd_opt(2024, 9, 30)
+    );
+}
+
+#[tokio::test]
+async fn online_returns_new_token_and_cookies() {
+    let server = MockServer::start(|_| {
+        Reply::json(json!({ "code": "0", "token_online": "new" })).ecs_cookies("t2", "a2")
+    })
+    .await;
+
+    let resp = server.client().online("old", "app").await.unwrap();
+
+    assert_eq!(resp.online_token, "new");
+    assert_eq!(resp.cookie, "ecs_token=t2; ecs_acc=a2");
+    let requests = server.requests();
+    assert_eq!(requests[0].path, ONLINE_PATH);
+    let form = requests[0].form();
+    assert_eq!(form["token_online"], "old");
+    assert_eq!(form["appId"], "app");
+}
+
+#[tokio::test]
+async fn online_with_expired_token_is_session_expired() {
+    let server = MockServer::start(|_| {
+        Reply::json(json!({ "code": "1", "desc": "token已失效, 请重新登录" }))
+    })
+    .await;
+
+    let err = server.client().online("old", "app").await.unwrap_err();
+    assert!(err.is_session_expired(), "{err:?}");
+}
+
+#[tokio::test]
+async fn online_without_cookies_fails() {
+    let server =
+        MockServer::start(|_| Reply::json(json!({ "code": "0", "token_online": "new" }))).await;
+
+    let err = server.client().online("old", "app").await.unwrap_err();
+    assert!(matches!(err, Error::MissingCookie(_)), "{err:?}");
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use china_unicom_rs::{ChinaUnicomClient, CookieJar, CredentialStore, Credentials, Error, Session};
+use common::{query_body, MockServer, Reply, Request};
+use serde_json::json;
+
+const QUERY_PATH: &str =
+    "/servicequerybusiness/operationservice/queryOcsPackageFlowLeftContentRevisedInJune";
+const ONLINE_PATH: &str = "/mobileService/onLine.htm";
+
+// 记录最后一次保存的凭据
+#[derive(Debug, Default)]
+struct MemoryStore(Mutex<Option<Credentials>>);
+
+impl CredentialStore for MemoryStore {
+    fn load(&self) -> china_unicom_rs::Result<Option<Credentials>> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn save(&self, credentials: &Credentials) -> china_unicom_rs::Result<()> {
+        *self.0.lock().unwrap() = Some(credentials.clone());
+        Ok(())
+    }
+}
+
+// 请求是否携带了 online() 下发的新 cookie, cookie jar 发送的顺序不固定
+fn has_new_cookies(request: &Request) -> bool {
+    let mut cookies: Vec<_> = request
+        .header("cookie")
+        .unwrap_or_default()
+        .split("; ")
+        .collect();
+    cookies.sort_unstable();
+    cookies == ["ecs_acc=new", "ecs_token=new"]
+}
+
+// online() 下发新的 cookie, 查询只接受新的 cookie
+fn unicom(request: &Request) -> Reply {
+    match request.path.as_str() {
+        ONLINE_PATH => {
+            Reply::json(json!({ "code": "0", "token_online": "token2" })).ecs_cookies("new", "new")
+        }
+        QUERY_PATH if has_new_cookies(request) => Reply::json(query_body()),
+        QUERY_PATH => Reply::json(json!({ "code": "999999", "desc": "登录已失效, 请重新登录" })),
+        _ => Reply::status(404, ""),
+    }
+}
+
+#[tokio::test]
+async fn query_refreshes_expired_cookie_and_retries() {
+    let server = MockServer::start(unicom).await;
+    let store = Arc::new(MemoryStore::default());
+    let mut session = Session::new(server.client(), "token1", "app")
+        .with_cookie("ecs_token=old; ecs_acc=old")
+        .unwrap()
+        .with_store(store.clone());
+
+    let data = session.query().await.unwrap();
+
+    assert_eq!(data.package_name, "测试套餐");
+    assert_eq!(server.hits(QUERY_PATH), 2);
+    assert_eq!(server.hits(ONLINE_PATH), 1);
+    assert_eq!(session.online_token(), "token2");
+    assert_eq!(session.cookie(), Some("ecs_token=new; ecs_acc=new"));
+
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.token_online, "token2");
+    assert_eq!(saved.cookie.as_deref(), Some("ecs_token=new; ecs_acc=new"));
+}
+
+#[tokio::test]
+async fn query_without_cookie_refreshes_first() {
+    let server = MockServer::start(unicom).await;
+    let mut session = Session::new(server.client(), "token1", "app");
+
+    session.query().await.unwrap();
+
+    assert_eq!(server.hits(ONLINE_PATH), 1);
+    assert_eq!(server.hits(QUERY_PATH), 1);
+    let requests = server.requests();
+    assert_eq!(requests[0].path, ONLINE_PATH);
+    assert_eq!(requests[0].form()["token_online"], "token1");
+}
+
+#[tokio::test]
+async fn query_retries_only_once() {
+    let server = MockServer::start(|request: &Request| match request.path.as_str() {
+        ONLINE_PATH => {
+            Reply::json(json!({ "code": "0", "token_online": "token2" })).ecs_cookies("new", "new")
+        }
+        _ => Reply::json(json!({ "code": "999999", "desc": "登录已失效, 请重新登录" })),
+    })
+    .await;
+    let mut session = Session::new(server.client(), "token1", "app")
+        .with_cookie("ecs_token=old; ecs_acc=old")
+        .unwrap();
+
+    let err = session.query().await.unwrap_err();
+
+    assert!(err.is_session_expired(), "{err:?}");
+    assert_eq!(server.hits(QUERY_PATH), 2);
+    assert_eq!(server.hits(ONLINE_PATH), 1);
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let server = MockServer::start(|request: &Request| match request.path.as_str() {
+        ONLINE_PATH => {
+            Reply::json(json!({ "code": "0", "token_online": "token2" })).ecs_cookies("new", "new")
+        }
+        _ => Reply::status(500, "Internal Server Error"),
+    })
+    .await;
+    let mut session = Session::new(server.client(), "token1", "app")
+        .with_cookie("ecs_token=old; ecs_acc=old")
+        .unwrap();
+
+    let err = session.query().await.unwrap_err();
+
+    assert!(matches!(err, Error::Http { status: 500, .. }), "{err:?}");
+    assert_eq!(server.hits(ONLINE_PATH), 0);
+}
+
+#[tokio::test]
+async fn cookie_jar_keeps_cookies_from_online() {
+    let server = MockServer::start(unicom).await;
+    let jar = CookieJar::new();
+    let client = ChinaUnicomClient::builder()
+        .base_url(&server.url)
+        .cookie_jar(jar.clone())
+        .build()
+        .unwrap();
+    let mut session = Session::new(client, "token1", "app");
+
+    session.query().await.unwrap();
+
+    assert!(jar.has_ecs_cookies());
+    assert_eq!(server.hits(ONLINE_PATH), 1);
+    assert_eq!(server.hits(QUERY_PATH), 1);
+}
+
+#[test]
+fn cookie_jar_with_existing_client_is_config_error() {
+    let err = ChinaUnicomClient::builder()
+        .client(reqwest::Client::new())
+        .cookie_jar(CookieJar::new())
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, Error::Config(_)), "{err:?}");
+}